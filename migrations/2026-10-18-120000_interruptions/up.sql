@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS interruptions (
+	id             INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	session_start  BIGINT NOT NULL, -- UNIX time at which the focus session started
+	at             BIGINT NOT NULL, -- UNIX time at which the interruption happened
+	kind           INTEGER NOT NULL, -- 0 for internal, 1 for external
+	note           TEXT,
+	session_id     INTEGER REFERENCES sessions (id) ON DELETE SET NULL, -- NULL until the session is saved
+	CHECK(kind >= 0 AND kind < 2)
+);
+CREATE INDEX IF NOT EXISTS interruptions_session ON interruptions (session_start);
+CREATE INDEX IF NOT EXISTS interruptions_session_id ON interruptions (session_id);
@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS sessions (
+	id                      INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+	started                 BIGINT NOT NULL, -- UNIX time at which the session started
+	ended                   BIGINT NOT NULL, -- UNIX time at which the session ended
+	kind                    INTEGER NOT NULL, -- 0 for focus, 1 for break
+	outcome                 INTEGER NOT NULL, -- 0 completed, 1 interrupted, 2 skipped
+	task_id                 INTEGER REFERENCES tasks (id) ON DELETE SET NULL,
+	internal_interruptions  INTEGER NOT NULL DEFAULT 0,
+	external_interruptions  INTEGER NOT NULL DEFAULT 0,
+	counted                 INTEGER NOT NULL DEFAULT 0, -- seconds counted, leaving out pauses
+	clover                  BOOLEAN NOT NULL DEFAULT 0, -- whether the session completed a clover
+	CHECK(started <= ended AND kind >= 0 AND kind < 2 AND outcome >= 0 AND outcome < 3)
+);
+CREATE INDEX IF NOT EXISTS sessions_started ON sessions (started);
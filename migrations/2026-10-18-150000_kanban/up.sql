@@ -0,0 +1,3 @@
+ALTER TABLE tasks ADD COLUMN status INTEGER NOT NULL DEFAULT 0 CHECK(status >= 0); -- column on the Kanban board
+ALTER TABLE tasks ADD COLUMN position INTEGER NOT NULL DEFAULT 0; -- order on the Kanban board, lowest first
+UPDATE tasks SET position = id;
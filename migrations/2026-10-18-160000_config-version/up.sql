@@ -0,0 +1 @@
+ALTER TABLE config ADD COLUMN version INTEGER NOT NULL DEFAULT 0; -- encoding of data, 0 from before encodings were versioned
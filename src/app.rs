@@ -1,33 +1,395 @@
-use clap::Parser;
-use crossterm::event::KeyEvent;
+use std::{collections::VecDeque, fmt::Display, time::SystemTime};
+
+use clap::{value_parser, Parser};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use diesel::SqliteConnection;
 use notify_rust::Notification;
+use time_fmt::FormattedTime;
 
 use crate::{
-	counter::Counter,
-	db::Task,
+	clock::Clock,
+	counter::{
+		BreakPolicy,
+		Counter,
+		CounterEvent,
+		CounterMode,
+		CounterSnapshot,
+		InterruptionKind,
+	},
+	db::{
+		delete_task,
+		get_tasks,
+		move_task,
+		set_task_status,
+		swap_tasks,
+		ConfigError,
+		ConfigResult,
+		ConfigValue,
+		DecodeError,
+		NewInterruption,
+		NewSession,
+		Task,
+		TaskError,
+	},
+	forms::{TaskForm, TaskPicker},
 	keys::*,
+	settings::{Setting, SettingEdit},
+	stats::Stats,
 	terminal::Terminal,
-	ui::{AppPage, Page},
+	ui::{AppPage, Page, Theme},
 };
 
+/// Number of counter actions that can be undone.
+const UNDO_LIMIT: usize = 32;
+/// Maximum number of characters that can be entered at a prompt.
+const PROMPT_LIMIT: usize = 64;
+
+/// Prefix of the names of the environment variables that settings can be
+/// given in, e.g. `FLUSSOMODORO_FOCUS_TIME`.
+pub const ENV_PREFIX: &str = "FLUSSOMODORO_";
+
+/// Columns of the Kanban board if none are configured.
+const DEFAULT_KANBAN_COLUMNS: [&str; 3] = ["To Do", "Doing", "Done"];
+
 #[derive(Clone, Default, Parser)]
 #[command(author, version, about)]
 /// A time management utility that understands your needs - press <h> in the
 /// application for more information
 pub struct AppOpts {
+	#[arg(short, long, value_enum)]
+	/// How focus sessions are timed [default: earned]
+	pub mode: Option<CounterMode>,
+	#[arg(short, long, value_parser = value_parser!(u32).range(1..))]
+	/// Number of seconds per focus session [default: 1500]
+	pub focus_time: Option<u32>,
+	#[arg(short = 'F', long)]
+	/// Minimum number of seconds for a flowtime session to count [default:
+	/// 600]
+	pub flow_min: Option<u32>,
 	#[arg(short, long)]
-	/// Number of seconds per focus session
-	pub focus_time: Option<u16>,
-	#[arg(short, long)]
-	/// Number of seconds of bonus break awarded for completing 4 focus
-	/// sessions
-	pub clover_break_bonus: Option<u16>,
-	#[arg(short, long)]
-	/// Whether or not to send notifications
-	pub notify: bool,
-	/// Whether or not to use ASCII art instead of gauges
-	#[arg(short, long)]
-	pub ascii: bool,
+	/// Number of seconds of bonus break awarded for completing a clover
+	/// [default: 900]
+	pub clover_break_bonus: Option<u32>,
+	#[arg(short = 's', long)]
+	/// Number of seconds of break after a focus session in classic mode
+	/// [default: 300]
+	pub short_break: Option<u32>,
+	#[arg(short = 'L', long)]
+	/// Number of seconds of break after a clover in classic mode [default:
+	/// 900]
+	pub long_break: Option<u32>,
+	#[arg(short = 'l', long, value_parser = value_parser!(u8).range(1..))]
+	/// Number of focus sessions per clover [default: 4]
+	pub clover_length: Option<u8>,
+	#[arg(short = 'r', long, value_parser = parse_ratio)]
+	/// Number of seconds of focus that earn one second of break, e.g. 5 or 2.5
+	/// [default: 5]
+	pub break_ratio: Option<f64>,
+	#[arg(short = 'P', long, value_enum)]
+	/// What to do when break runs out during a break [default: reset]
+	pub break_policy: Option<BreakPolicy>,
+	#[arg(long, num_args = 0..=1, default_missing_value = "true")]
+	/// Whether to start a break as soon as a focus session is completed
+	/// [default: false]
+	pub auto_break: Option<bool>,
+	#[arg(long, num_args = 0..=1, default_missing_value = "true")]
+	/// Whether to start focusing as soon as a break is over [default: false]
+	pub auto_focus: Option<bool>,
+	#[arg(short = 'w', long, value_delimiter = ',', num_args = 0..)]
+	/// Seconds of break remaining at which to warn that it is almost over,
+	/// e.g. 300,60,10 [default: 30]
+	pub break_warnings: Option<Vec<u32>>,
+	#[arg(short = 'W', long, value_delimiter = ',', num_args = 0..)]
+	/// Seconds of focus remaining at which to warn that it is almost over
+	/// [default: 300]
+	pub focus_warnings: Option<Vec<u32>>,
+	#[arg(short = 'k', long, value_delimiter = ',', num_args = 1..)]
+	/// Names of the columns of the Kanban board, from first to last [default:
+	/// "To Do,Doing,Done"]
+	pub kanban_columns: Option<Vec<String>>,
+	#[arg(short, long, num_args = 0..=1, default_missing_value = "true")]
+	/// Whether or not to send notifications [default: false]
+	pub notify: Option<bool>,
+	#[arg(short, long, num_args = 0..=1, default_missing_value = "true")]
+	/// Whether or not to use ASCII art instead of gauges [default: false]
+	pub ascii: Option<bool>,
+	#[arg(short, long, value_enum)]
+	/// Colours to draw the interface in [default: dark]
+	pub theme: Option<Theme>,
+	#[arg(long)]
+	/// Save the settings given on the command line as the defaults for later
+	/// runs
+	pub save: bool,
+}
+
+impl AppOpts {
+	/// Settings given in environment variables, named after the settings
+	/// with [`ENV_PREFIX`] and taking the same values as on the command line,
+	/// e.g. `FLUSSOMODORO_BREAK_WARNINGS=300,60`.
+	pub fn from_env() -> Result<Self, clap::Error> {
+		AppOpts::from_vars(std::env::vars())
+	}
+
+	/// Settings given in the environment variables `vars`, as in
+	/// [`AppOpts::from_env`]. Variables that are not settings are ignored.
+	pub fn from_vars<I: IntoIterator<Item = (String, String)>>(
+		vars: I,
+	) -> Result<Self, clap::Error> {
+		let args = vars.into_iter().filter_map(|(var, value)| {
+			let key = var.strip_prefix(ENV_PREFIX)?.to_lowercase();
+			let setting = Setting::ALL.iter().find(|x| x.key() == key)?;
+			Some(format!("--{}={value}", setting.key().replace('_', "-")))
+		});
+		AppOpts::try_parse_from(
+			std::iter::once("flussomodoro".into()).chain(args),
+		)
+	}
+
+	/// Fills in the settings not given on the command line from those given
+	/// in `env`, then from those saved in previous runs, leaving the rest to
+	/// their defaults. With `--save`, the settings given on the command line
+	/// are saved first, as the defaults for later runs.
+	pub fn load_config(
+		&mut self,
+		conn: &mut SqliteConnection,
+		env: &AppOpts,
+	) -> ConfigResult<()> {
+		if self.save {
+			self.save_config(conn)?;
+		}
+		fill_config_value(conn, Setting::Mode, &mut self.mode, &env.mode)?;
+		fill_number_config_value(
+			conn,
+			Setting::FocusTime,
+			&mut self.focus_time,
+			&env.focus_time,
+		)?;
+		fill_number_config_value(
+			conn,
+			Setting::FlowMin,
+			&mut self.flow_min,
+			&env.flow_min,
+		)?;
+		fill_number_config_value(
+			conn,
+			Setting::ShortBreak,
+			&mut self.short_break,
+			&env.short_break,
+		)?;
+		fill_number_config_value(
+			conn,
+			Setting::LongBreak,
+			&mut self.long_break,
+			&env.long_break,
+		)?;
+		fill_number_config_value(
+			conn,
+			Setting::CloverLength,
+			&mut self.clover_length,
+			&env.clover_length,
+		)?;
+		fill_number_config_value(
+			conn,
+			Setting::CloverBreakBonus,
+			&mut self.clover_break_bonus,
+			&env.clover_break_bonus,
+		)?;
+		fill_number_config_value(
+			conn,
+			Setting::BreakRatio,
+			&mut self.break_ratio,
+			&env.break_ratio,
+		)?;
+		fill_config_value(
+			conn,
+			Setting::BreakPolicy,
+			&mut self.break_policy,
+			&env.break_policy,
+		)?;
+		fill_config_value(
+			conn,
+			Setting::AutoBreak,
+			&mut self.auto_break,
+			&env.auto_break,
+		)?;
+		fill_config_value(
+			conn,
+			Setting::AutoFocus,
+			&mut self.auto_focus,
+			&env.auto_focus,
+		)?;
+		fill_config_value(
+			conn,
+			Setting::BreakWarnings,
+			&mut self.break_warnings,
+			&env.break_warnings,
+		)?;
+		fill_config_value(
+			conn,
+			Setting::FocusWarnings,
+			&mut self.focus_warnings,
+			&env.focus_warnings,
+		)?;
+		fill_config_value(
+			conn,
+			Setting::KanbanColumns,
+			&mut self.kanban_columns,
+			&env.kanban_columns,
+		)?;
+		fill_config_value(
+			conn,
+			Setting::Notify,
+			&mut self.notify,
+			&env.notify,
+		)?;
+		fill_config_value(conn, Setting::Ascii, &mut self.ascii, &env.ascii)?;
+		fill_config_value(conn, Setting::Theme, &mut self.theme, &env.theme)
+	}
+
+	/// Saves the settings that are set, as the defaults for later runs.
+	pub fn save_config(&self, conn: &mut SqliteConnection) -> ConfigResult<()> {
+		Setting::ALL
+			.iter()
+			.try_for_each(|&setting| self.save_setting(conn, setting))
+	}
+
+	/// Saves `setting`, if it is set, as the default for later runs.
+	pub fn save_setting(
+		&self,
+		conn: &mut SqliteConnection,
+		setting: Setting,
+	) -> ConfigResult<()> {
+		match setting {
+			Setting::Mode => save_config_value(conn, setting, &self.mode),
+			Setting::FocusTime => {
+				save_config_value(conn, setting, &self.focus_time)
+			}
+			Setting::FlowMin => {
+				save_config_value(conn, setting, &self.flow_min)
+			}
+			Setting::ShortBreak => {
+				save_config_value(conn, setting, &self.short_break)
+			}
+			Setting::LongBreak => {
+				save_config_value(conn, setting, &self.long_break)
+			}
+			Setting::CloverLength => {
+				save_config_value(conn, setting, &self.clover_length)
+			}
+			Setting::CloverBreakBonus => {
+				save_config_value(conn, setting, &self.clover_break_bonus)
+			}
+			Setting::BreakRatio => {
+				save_config_value(conn, setting, &self.break_ratio)
+			}
+			Setting::BreakPolicy => {
+				save_config_value(conn, setting, &self.break_policy)
+			}
+			Setting::AutoBreak => {
+				save_config_value(conn, setting, &self.auto_break)
+			}
+			Setting::AutoFocus => {
+				save_config_value(conn, setting, &self.auto_focus)
+			}
+			Setting::BreakWarnings => {
+				save_config_value(conn, setting, &self.break_warnings)
+			}
+			Setting::FocusWarnings => {
+				save_config_value(conn, setting, &self.focus_warnings)
+			}
+			Setting::KanbanColumns => {
+				save_config_value(conn, setting, &self.kanban_columns)
+			}
+			Setting::Notify => save_config_value(conn, setting, &self.notify),
+			Setting::Ascii => save_config_value(conn, setting, &self.ascii),
+			Setting::Theme => save_config_value(conn, setting, &self.theme),
+		}
+	}
+
+	/// Names of the columns of the Kanban board, by `status`.
+	pub fn kanban_columns(&self) -> Vec<&str> {
+		match &self.kanban_columns {
+			Some(columns) if !columns.is_empty() => {
+				columns.iter().map(String::as_str).collect()
+			}
+			_ => DEFAULT_KANBAN_COLUMNS.to_vec(),
+		}
+	}
+}
+
+fn fill_config_value<T: ConfigValue + Clone>(
+	conn: &mut SqliteConnection,
+	setting: Setting,
+	value: &mut Option<T>,
+	env: &Option<T>,
+) -> ConfigResult<()> {
+	fill_checked_config_value(conn, setting, value, env, |_| true)
+}
+
+/// Fills in a number like [`fill_config_value`], taking a saved value only if
+/// the command line would take it too.
+fn fill_number_config_value<T: ConfigValue + Clone + Display>(
+	conn: &mut SqliteConnection,
+	setting: Setting,
+	value: &mut Option<T>,
+	env: &Option<T>,
+) -> ConfigResult<()> {
+	fill_checked_config_value(conn, setting, value, env, |stored| {
+		let arg = format!("--{}={stored}", setting.key().replace('_', "-"));
+		AppOpts::try_parse_from(["flussomodoro", &arg]).is_ok()
+	})
+}
+
+/// Fills in a setting like [`fill_config_value`], taking a saved value only
+/// if it is `valid`.
+fn fill_checked_config_value<T: ConfigValue + Clone, F: Fn(&T) -> bool>(
+	conn: &mut SqliteConnection,
+	setting: Setting,
+	value: &mut Option<T>,
+	env: &Option<T>,
+	valid: F,
+) -> ConfigResult<()> {
+	if value.is_none() {
+		value.clone_from(env);
+	}
+	if value.is_none() {
+		let key = setting.key().to_string();
+		let stored = T::get_from(conn, key.clone()).and_then(|stored| {
+			match valid(&stored) {
+				true => Ok(stored),
+				false => Err(ConfigError::Decode {
+					key,
+					source: DecodeError::OutOfRange { kind: setting.key() },
+				}),
+			}
+		});
+		*value = match stored {
+			Ok(stored) => Some(stored),
+			// a value that cannot be read back is as good as unset
+			Err(err @ ConfigError::Database(_)) => return Err(err),
+			Err(_) => None,
+		};
+	}
+	Ok(())
+}
+
+fn save_config_value<T: ConfigValue + Clone>(
+	conn: &mut SqliteConnection,
+	setting: Setting,
+	value: &Option<T>,
+) -> ConfigResult<()> {
+	match value {
+		Some(value) => value.clone().set_into(conn, setting.key().to_string()),
+		None => Ok(()),
+	}
+}
+
+pub(crate) fn parse_ratio(arg: &str) -> Result<f64, String> {
+	match arg.parse::<f64>() {
+		Ok(ratio) if ratio.is_finite() && ratio > 0.0 => Ok(ratio),
+		Ok(_) => Err("ratio must be a positive number".to_string()),
+		Err(err) => Err(err.to_string()),
+	}
 }
 
 #[derive(Default)]
@@ -37,6 +399,18 @@ pub struct App {
 	pub opts: AppOpts,
 	pub tasks: Vec<Task>,
 	pub selected_task: Option<i32>,
+	pub prompt: Option<Prompt>,
+	pub task_form: Option<TaskForm>,
+	pub task_picker: Option<TaskPicker>,
+	pub setting_edit: Option<SettingEdit>,
+	/// Statistics on the session history, as of when they were last loaded
+	pub stats: Stats,
+	/// Copies of the counter from before each undoable action, latest last
+	undo: VecDeque<Counter>,
+	/// Interruptions logged since they were last taken to be saved
+	interruptions: Vec<NewInterruption>,
+	/// Sessions ended since they were last taken to be saved
+	sessions: Vec<NewSession>,
 }
 
 impl App {
@@ -52,27 +426,119 @@ impl App {
 		}
 	}
 
-	pub fn handle_key_event(&mut self, event: KeyEvent) -> bool {
+	pub fn handle_key_event(
+		&mut self,
+		event: KeyEvent,
+		conn: &mut SqliteConnection,
+	) -> bool {
+		if self.prompt.is_some() {
+			return self.handle_prompt_key_event(event, conn);
+		}
+		if self.task_picker.is_some() {
+			return self.handle_task_picker_key_event(event);
+		}
+		if self.task_form.is_some() {
+			return self.handle_task_form_key_event(event, conn);
+		}
+		if self.setting_edit.is_some() {
+			return self.handle_setting_edit_key_event(event, conn);
+		}
+		let page_handled = match self.page {
+			AppPage::EisenhowerMat(_) => {
+				self.handle_matrix_key_event(event, conn)
+			}
+			AppPage::Kanban(_) => self.handle_kanban_key_event(event, conn),
+			AppPage::Tasks(_) => self.handle_tasks_key_event(event),
+			AppPage::Settings(_) => self.handle_settings_key_event(event, conn),
+			_ => false,
+		};
+		if page_handled {
+			return false;
+		}
+
 		match (event.modifiers, event.code) {
 			SIGINT | QUIT => true,
 			PAUSE => {
-				self.counter.work_state_mut().toggle_active();
+				self.act_on_counter(|counter, events| {
+					events.extend(
+						counter.toggle_active().map(CounterEvent::Transition),
+					)
+				});
 				false
 			}
 			BREAK => {
-				self.counter.work_state_mut().toggle_break();
+				self.act_on_counter(|counter, events| {
+					counter.toggle_break(|event| events.push(event))
+				});
+				false
+			}
+			END => {
+				self.act_on_counter(|counter, events| {
+					counter.end_session(|event| events.push(event))
+				});
+				false
+			}
+			ABANDON => {
+				self.act_on_counter(|counter, events| {
+					counter.abandon(|event| events.push(event))
+				});
+				false
+			}
+			SKIP => {
+				self.act_on_counter(|counter, events| {
+					counter.skip_break(|event| events.push(event))
+				});
+				false
+			}
+			UNDO => {
+				if let Some(earlier) = self.undo.pop_back() {
+					// settings changed since are kept, only progress goes back
+					let opts = self.opts.clone();
+					self.update_counter(|counter, events| {
+						events.extend(
+							counter
+								.rewind(earlier)
+								.map(CounterEvent::Transition),
+						);
+						counter.reconfigure(&opts);
+					});
+				}
+				false
+			}
+			INTERRUPT_INTERNAL => {
+				self.interrupt(InterruptionKind::Internal);
+				false
+			}
+			INTERRUPT_EXTERNAL => {
+				self.interrupt(InterruptionKind::External);
+				false
+			}
+			PICK_TASK => {
+				self.task_picker =
+					Some(TaskPicker::load(conn).expect("Failed to load tasks"));
+				false
+			}
+			WORK_ON_TASK => {
+				if let Some(task) = self.highlighted_task() {
+					self.selected_task = (self.selected_task != Some(task.id))
+						.then_some(task.id);
+				}
 				false
 			}
 			HELP => {
 				self.page = self.page.toggle_help();
 				false
 			}
-			VI_DOWN => {
-				self.page.scroll_by(1);
+			SETTINGS => {
+				self.page = self.page.toggle_settings();
+				false
+			}
+			VI_DOWN | DOWN => {
+				self.page.scroll_by(1, self.page_rows());
 				false
 			}
-			VI_UP => {
-				self.page.scroll_by(-1);
+			VI_UP | UP => {
+				self.page.scroll_by(-1, self.page_rows());
 				false
 			}
 			NEXT => {
@@ -87,14 +553,524 @@ impl App {
 		}
 	}
 
+	/// Number of rows listed on the current page, or in the current part of it.
+	fn page_rows(&self) -> usize {
+		match self.page {
+			AppPage::EisenhowerMat(matrix) => {
+				self.quadrant_tasks(matrix.quadrant()).count()
+			}
+			AppPage::Kanban(board) => self.column_tasks(board.column()).len(),
+			AppPage::Tasks(_) => self.tasks.len(),
+			AppPage::Settings(_) => Setting::ALL.len(),
+			_ => 0,
+		}
+	}
+
+	/// Tasks in quadrant `box_cat` of the Eisenhower matrix.
+	pub fn quadrant_tasks(&self, box_cat: i32) -> impl Iterator<Item = &Task> {
+		self.tasks.iter().filter(move |task| task.box_cat == box_cat)
+	}
+
+	/// Tasks in column `status` of the Kanban board, in order. Tasks in columns
+	/// past the last, which can be left over when there used to be more
+	/// columns, are shown in the last.
+	pub fn column_tasks(&self, status: i32) -> Vec<&Task> {
+		let last = self.opts.kanban_columns().len() as i32 - 1;
+		let mut tasks: Vec<&Task> = self
+			.tasks
+			.iter()
+			.filter(|task| task.status.min(last) == status)
+			.collect();
+		tasks.sort_by_key(|task| task.position);
+		tasks
+	}
+
+	/// Handles the keys specific to the Kanban page, returning whether the key
+	/// was one of them.
+	fn handle_kanban_key_event(
+		&mut self,
+		event: KeyEvent,
+		conn: &mut SqliteConnection,
+	) -> bool {
+		let AppPage::Kanban(board) = &mut self.page else {
+			return false;
+		};
+		let columns = self.opts.kanban_columns().len() as i32;
+		let key = (event.modifiers, event.code);
+		match key {
+			PREV_COLUMN => board.focus_by(-1, columns),
+			NEXT_COLUMN => board.focus_by(1, columns),
+			MOVE_CARD_LEFT | MOVE_CARD_RIGHT | MOVE_CARD_UP
+			| MOVE_CARD_DOWN => self.move_card(key, conn),
+			_ => return false,
+		}
+		true
+	}
+
+	/// Moves the selected card on the Kanban board as `key` asks, if it can
+	/// be moved that way, keeping it selected.
+	fn move_card(&mut self, key: KeyPair, conn: &mut SqliteConnection) {
+		let AppPage::Kanban(board) = self.page else {
+			return;
+		};
+		let columns = self.opts.kanban_columns().len() as i32;
+		let column = self.column_tasks(board.column());
+		let Some(task) = column.get(board.selected()) else {
+			return;
+		};
+		let row = board.selected();
+		let moved = match key {
+			MOVE_CARD_LEFT if board.column() > 0 => {
+				set_task_status(conn, task.id, board.column() - 1)
+			}
+			MOVE_CARD_RIGHT if board.column() < columns - 1 => {
+				set_task_status(conn, task.id, board.column() + 1)
+			}
+			MOVE_CARD_UP if row > 0 => {
+				swap_tasks(conn, task.id, column[row - 1].id)
+			}
+			MOVE_CARD_DOWN if row + 1 < column.len() => {
+				swap_tasks(conn, task.id, column[row + 1].id)
+			}
+			_ => return,
+		};
+		let task = moved.expect("Failed to move task");
+		self.load_tasks(conn);
+		let status = task.status.min(columns - 1);
+		let row =
+			self.column_tasks(status).iter().position(|x| x.id == task.id);
+		if let AppPage::Kanban(board) = &mut self.page {
+			board.focus(status, row.unwrap_or(0));
+		}
+	}
+
+	/// Handles the keys specific to the Eisenhower matrix page, returning
+	/// whether the key was one of them.
+	fn handle_matrix_key_event(
+		&mut self,
+		event: KeyEvent,
+		conn: &mut SqliteConnection,
+	) -> bool {
+		let AppPage::EisenhowerMat(matrix) = &mut self.page else {
+			return false;
+		};
+		let key = (event.modifiers, event.code);
+		match key {
+			PREV_QUADRANT => matrix.focus_by(-1),
+			NEXT_QUADRANT => matrix.focus_by(1),
+			_ => {
+				let Some(box_cat) = TO_QUADRANT.iter().position(|&x| x == key)
+				else {
+					return false;
+				};
+				let matrix = *matrix;
+				let Some(task) = self
+					.quadrant_tasks(matrix.quadrant())
+					.nth(matrix.selected())
+				else {
+					return true;
+				};
+				let task = move_task(conn, task.id, box_cat as i32)
+					.expect("Failed to move task");
+				self.load_tasks(conn);
+				let row = self
+					.quadrant_tasks(task.box_cat)
+					.position(|x| x.id == task.id);
+				if let AppPage::EisenhowerMat(matrix) = &mut self.page {
+					matrix.focus(task.box_cat, row.unwrap_or(0));
+				}
+			}
+		}
+		true
+	}
+
+	/// Handles the keys specific to the Tasks page, returning whether the key
+	/// was one of them.
+	fn handle_tasks_key_event(&mut self, event: KeyEvent) -> bool {
+		match (event.modifiers, event.code) {
+			NEW_TASK => self.task_form = Some(TaskForm::new()),
+			EDIT_TASK => {
+				self.task_form = self.highlighted_task().map(TaskForm::edit)
+			}
+			DELETE_TASK => {
+				let Some(task) = self.highlighted_task() else {
+					return true;
+				};
+				self.prompt = Some(Prompt {
+					title: format!(
+						"Delete task '{}'? Type y to confirm",
+						task.name
+					),
+					input: String::new(),
+					purpose: PromptPurpose::DeleteTask(task.id),
+				});
+			}
+			(_, _) => return false,
+		}
+		true
+	}
+
+	fn handle_task_form_key_event(
+		&mut self,
+		event: KeyEvent,
+		conn: &mut SqliteConnection,
+	) -> bool {
+		let Some(form) = &mut self.task_form else {
+			return false;
+		};
+		match (event.modifiers, event.code) {
+			SIGINT => return true,
+			SUBMIT => match form.save(conn) {
+				Ok(task) => {
+					self.task_form = None;
+					self.load_tasks(conn);
+					self.highlight_task(task.id);
+				}
+				Err(TaskError::Database(err)) => {
+					panic!("Failed to save task: {err}")
+				}
+				Err(err) => form.error = Some(err.to_string()),
+			},
+			CANCEL => self.task_form = None,
+			NEXT | DOWN => form.move_field(1),
+			PREV | UP => form.move_field(-1),
+			LEFT => form.cycle_quadrant(-1),
+			RIGHT => form.cycle_quadrant(1),
+			ERASE => {
+				form.input().map(String::pop);
+			}
+			(KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char(c)) => {
+				if let Some(input) = form
+					.input()
+					.filter(|input| input.chars().count() < PROMPT_LIMIT)
+				{
+					input.push(c);
+				}
+			}
+			(_, _) => (),
+		}
+		false
+	}
+
+	/// Handles the keys specific to the Settings page, returning whether the
+	/// key was one of them.
+	fn handle_settings_key_event(
+		&mut self,
+		event: KeyEvent,
+		conn: &mut SqliteConnection,
+	) -> bool {
+		let AppPage::Settings(page) = self.page else {
+			return false;
+		};
+		let setting = Setting::ALL[page.selected()];
+		match (event.modifiers, event.code) {
+			SUBMIT if !setting.is_choice() => {
+				self.setting_edit = Some(SettingEdit {
+					setting,
+					input: setting.value(&self.opts, &self.counter),
+					error: None,
+				})
+			}
+			SUBMIT | RIGHT => {
+				setting.cycle(&mut self.opts, 1);
+				self.apply_setting(conn, setting);
+			}
+			LEFT => {
+				setting.cycle(&mut self.opts, -1);
+				self.apply_setting(conn, setting);
+			}
+			(_, _) => return false,
+		}
+		true
+	}
+
+	fn handle_setting_edit_key_event(
+		&mut self,
+		event: KeyEvent,
+		conn: &mut SqliteConnection,
+	) -> bool {
+		let Some(edit) = &mut self.setting_edit else {
+			return false;
+		};
+		match (event.modifiers, event.code) {
+			SIGINT => return true,
+			SUBMIT => match edit.setting.set(&mut self.opts, &edit.input) {
+				Ok(()) => {
+					let setting = edit.setting;
+					self.setting_edit = None;
+					self.apply_setting(conn, setting);
+				}
+				Err(error) => edit.error = Some(error),
+			},
+			CANCEL => self.setting_edit = None,
+			ERASE => {
+				edit.input.pop();
+			}
+			(KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char(c))
+				if edit.input.chars().count() < PROMPT_LIMIT =>
+			{
+				edit.input.push(c)
+			}
+			(_, _) => (),
+		}
+		false
+	}
+
+	/// Applies the settings to the counter, and saves the changed `setting`
+	/// for later runs.
+	fn apply_setting(&mut self, conn: &mut SqliteConnection, setting: Setting) {
+		self.counter.reconfigure(&self.opts);
+		self.opts.save_setting(conn, setting).expect("Failed to save settings");
+	}
+
+	fn handle_task_picker_key_event(&mut self, event: KeyEvent) -> bool {
+		let Some(picker) = &mut self.task_picker else {
+			return false;
+		};
+		match (event.modifiers, event.code) {
+			SIGINT => return true,
+			SUBMIT => {
+				if let Some(task) = picker.picked() {
+					self.selected_task = Some(task.id);
+				}
+				self.task_picker = None;
+			}
+			CANCEL => self.task_picker = None,
+			DOWN => picker.select_by(1),
+			UP => picker.select_by(-1),
+			ERASE => picker.pop(),
+			(KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char(c)) => {
+				picker.push(c)
+			}
+			(_, _) => (),
+		}
+		false
+	}
+
+	/// The task under the cursor, if the page lists tasks.
+	fn highlighted_task(&self) -> Option<&Task> {
+		match self.page {
+			AppPage::EisenhowerMat(matrix) => {
+				self.quadrant_tasks(matrix.quadrant()).nth(matrix.selected())
+			}
+			AppPage::Kanban(board) => {
+				self.column_tasks(board.column()).get(board.selected()).copied()
+			}
+			AppPage::Tasks(page) => self.tasks.get(page.selected()),
+			_ => None,
+		}
+	}
+
+	/// The task being worked on, which completed poms are credited to.
+	pub fn current_task(&self) -> Option<&Task> {
+		let task_id = self.selected_task?;
+		self.tasks.iter().find(|task| task.id == task_id)
+	}
+
+	/// Moves the cursor on the Tasks page to the task with id `task_id`.
+	fn highlight_task(&mut self, task_id: i32) {
+		let row = self.tasks.iter().position(|task| task.id == task_id);
+		if let (AppPage::Tasks(page), Some(row)) = (&mut self.page, row) {
+			page.select(row);
+		}
+	}
+
+	/// Reloads the tasks from the database.
+	pub fn load_tasks(&mut self, conn: &mut SqliteConnection) {
+		self.tasks = get_tasks(conn).expect("Failed to load tasks");
+		if let AppPage::Tasks(page) = &mut self.page {
+			page.select(
+				page.selected().min(self.tasks.len().saturating_sub(1)),
+			);
+		}
+	}
+
+	fn handle_prompt_key_event(
+		&mut self,
+		event: KeyEvent,
+		conn: &mut SqliteConnection,
+	) -> bool {
+		let Some(prompt) = &mut self.prompt else {
+			return false;
+		};
+		match (event.modifiers, event.code) {
+			SIGINT => {
+				self.close_prompt(false, conn);
+				return true;
+			}
+			SUBMIT => self.close_prompt(true, conn),
+			CANCEL => self.close_prompt(false, conn),
+			ERASE => {
+				prompt.input.pop();
+			}
+			(KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char(c))
+				if prompt.input.chars().count() < PROMPT_LIMIT =>
+			{
+				prompt.input.push(c)
+			}
+			(_, _) => (),
+		}
+		false
+	}
+
+	/// Closes the prompt, acting on its input if it was `submitted`.
+	fn close_prompt(&mut self, submitted: bool, conn: &mut SqliteConnection) {
+		let Some(prompt) = self.prompt.take() else {
+			return;
+		};
+		let input = Some(prompt.input.trim().to_string())
+			.filter(|input| submitted && !input.is_empty());
+		match prompt.purpose {
+			// the interruption is logged either way, just without a note
+			PromptPurpose::InterruptionNote(mut interruption) => {
+				interruption.note = input;
+				self.interruptions.push(interruption);
+			}
+			PromptPurpose::DeleteTask(task_id) => {
+				if input.is_some_and(|input| input.eq_ignore_ascii_case("y")) {
+					delete_task(conn, task_id).expect("Failed to delete task");
+					if self.selected_task == Some(task_id) {
+						self.selected_task = None;
+					}
+					self.load_tasks(conn);
+				}
+			}
+		}
+	}
+
+	/// Counts an interruption of the current focus session, and prompts for a
+	/// note on it.
+	///
+	/// Like an ended phase, the interruption is recorded, which undoing cannot
+	/// take back, so nothing from before it can be undone any more.
+	fn interrupt(&mut self, kind: InterruptionKind) {
+		let Some(at) = self.counter.interrupt(kind) else {
+			return;
+		};
+		self.undo.clear();
+		let session_start = self.counter.session_start().unwrap_or(at);
+		self.prompt = Some(Prompt {
+			title: format!("Note on {kind} interruption (optional)"),
+			input: String::new(),
+			purpose: PromptPurpose::InterruptionNote(NewInterruption::new(
+				session_start,
+				at,
+				kind,
+			)),
+		});
+	}
+
+	/// Takes the interruptions logged since this was last called, for saving.
+	pub fn take_interruptions(&mut self) -> Vec<NewInterruption> {
+		std::mem::take(&mut self.interruptions)
+	}
+
+	/// Takes the sessions ended since this was last called, for saving.
+	pub fn take_sessions(&mut self) -> Vec<NewSession> {
+		std::mem::take(&mut self.sessions)
+	}
+
+	/// Advances the counter to the current time on `clock`, handling whatever
+	/// happened in the meantime.
+	pub fn tick<C: Clock>(&mut self, clock: &C) {
+		self.update_counter(|counter, events| {
+			counter.tick(clock, |event| events.push(event))
+		});
+	}
+
+	/// Restores the counter from a `snapshot` taken in a previous run,
+	/// handling whatever happened while away up to `now`.
+	pub fn restore_counter(
+		&mut self,
+		snapshot: CounterSnapshot,
+		now: SystemTime,
+	) {
+		self.update_counter(|counter, events| {
+			counter.restore(snapshot, now, |event| events.push(event))
+		});
+	}
+
+	/// Updates the counter with `f` like [`App::update_counter`], remembering
+	/// the counter from before so that the update can be undone, unless it
+	/// ended a phase.
+	fn act_on_counter<F: FnOnce(&mut Counter, &mut Vec<CounterEvent>)>(
+		&mut self,
+		f: F,
+	) {
+		let before = self.counter.clone();
+		let ended_phase = self.update_counter(f);
+		if self.counter != before && !ended_phase {
+			if self.undo.len() == UNDO_LIMIT {
+				self.undo.pop_front();
+			}
+			self.undo.push_back(before);
+		}
+	}
+
+	/// Updates the counter with `f`, then handles the events it produced,
+	/// returning whether any phase ended.
+	///
+	/// An ended phase is recorded in the history, which undoing cannot take
+	/// back, so nothing from before it can be undone any more.
+	fn update_counter<F: FnOnce(&mut Counter, &mut Vec<CounterEvent>)>(
+		&mut self,
+		f: F,
+	) -> bool {
+		let mut events = Vec::new();
+		f(&mut self.counter, &mut events);
+		let ended_phase = events
+			.iter()
+			.any(|event| matches!(event, CounterEvent::PhaseEnded(_)));
+		if ended_phase {
+			self.undo.clear();
+		}
+		events.into_iter().for_each(|event| self.handle_counter_event(event));
+		ended_phase
+	}
+
+	fn handle_counter_event(&mut self, event: CounterEvent) {
+		match event {
+			CounterEvent::Notify(msg)
+				if self.opts.notify.unwrap_or_default() =>
+			{
+				Notification::from(msg).show().unwrap();
+			}
+			CounterEvent::PhaseEnded(summary) => {
+				self.sessions
+					.extend(NewSession::new(&summary, self.selected_task));
+			}
+			_ => (),
+		}
+	}
+
 	pub fn draw_with(&self, terminal: &mut Terminal) {
 		terminal.0.draw(|f| self.page.render(f.area(), f, self)).unwrap();
 	}
 }
 
+/// A line of text being entered by the user, which takes all key input until
+/// it is submitted or cancelled.
+pub struct Prompt {
+	pub title: String,
+	pub input: String,
+	purpose: PromptPurpose,
+}
+
+/// What the input to a [`Prompt`] is for.
+enum PromptPurpose {
+	InterruptionNote(NewInterruption),
+	/// Confirmation to delete the task with the given id
+	DeleteTask(i32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum AppNotification {
-	BreakAlmostOver,
-	BreakOver,
+	/// Seconds of break remaining
+	BreakAlmostOver(u32),
+	BreakOver(BreakPolicy),
+	FocusHalfway,
+	/// Seconds of focus remaining
+	FocusAlmostOver(u32),
 	PomComplete,
 	CloverComplete,
 }
@@ -104,12 +1080,33 @@ impl From<AppNotification> for Notification {
 		let mut notif = Notification::new();
 		notif.summary("Flussomodoro").auto_icon();
 		match msg {
-			AppNotification::BreakAlmostOver => {
-				notif.body("Your break is almost over! Get ready!")
+			AppNotification::BreakAlmostOver(remaining) => {
+				notif.body(&format!(
+					"Your break ends in {}! Get ready!",
+					FormattedTime::from(remaining)
+				))
 			}
-			AppNotification::BreakOver => {
+			AppNotification::BreakOver(BreakPolicy::Reset) => {
 				notif.body("Your break expired and your session has reset :(")
 			}
+			AppNotification::BreakOver(BreakPolicy::Resume) => {
+				notif.body("Your break is over! Back to focus!")
+			}
+			AppNotification::BreakOver(BreakPolicy::Overdraft) => notif.body(
+				"Your break is over! Any more is borrowed from your next focus",
+			),
+			AppNotification::BreakOver(BreakPolicy::Pause) => {
+				notif.body("Your break is over! Resume when you're ready")
+			}
+			AppNotification::FocusHalfway => {
+				notif.body("Halfway through your session! Keep it up!")
+			}
+			AppNotification::FocusAlmostOver(remaining) => {
+				notif.body(&format!(
+					"{} of focus left! Finish strong!",
+					FormattedTime::from(remaining)
+				))
+			}
 			AppNotification::CloverComplete => {
 				notif.body("Clover complete! Great job!")
 			}
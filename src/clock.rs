@@ -0,0 +1,53 @@
+use std::{
+	cell::Cell,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// A source of wall-clock time.
+///
+/// The counter measures elapsed time against a clock rather than counting
+/// ticks, so that missed ticks and suspends are accounted for. Implementing
+/// this trait allows time to be driven by something other than the system.
+pub trait Clock {
+	fn now(&self) -> SystemTime;
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+	fn now(&self) -> SystemTime {
+		SystemTime::now()
+	}
+}
+
+/// A clock that only moves when told to, allowing long stretches of time to be
+/// simulated instantly and deterministically.
+#[derive(Clone, Debug)]
+pub struct MockClock(Cell<SystemTime>);
+
+impl MockClock {
+	pub fn new(start: SystemTime) -> Self {
+		MockClock(Cell::new(start))
+	}
+
+	pub fn advance(&self, by: Duration) {
+		self.0.set(self.0.get() + by);
+	}
+
+	pub fn set(&self, to: SystemTime) {
+		self.0.set(to);
+	}
+}
+
+impl Default for MockClock {
+	fn default() -> Self {
+		MockClock::new(UNIX_EPOCH)
+	}
+}
+
+impl Clock for MockClock {
+	fn now(&self) -> SystemTime {
+		self.0.get()
+	}
+}
@@ -1,30 +1,107 @@
+use std::{
+	fmt::{self, Display, Formatter},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use clap::ValueEnum;
 use derivative::Derivative;
 
-use crate::app::{AppNotification, AppOpts};
+use crate::{
+	app::{AppNotification, AppOpts},
+	clock::Clock,
+};
 
 // logic:
-// every 5 seconds of focus, the user gets 1 second of break.
+// every 5 seconds of focus, the user gets 1 second of break (by default - the
+// ratio is configurable and need not be whole).
 // that's 5 minutes of break per 25 minutes of focus.
 // once 25 minutes has elapsed, a focus session is complete.
-// once the user has completed 4 focus sessions, they get 15 minutes of break.
-// if the user runs out of break, their current focus session resets.
+// once the user has completed a clover of focus sessions (4 by default), they
+// get 15 minutes of break.
+// if the user runs out of break, their current focus session resets (by
+// default - see `BreakPolicy` for the alternatives).
+//
+// in flowtime mode, focus counts up with no fixed end instead, and the session
+// is ended by the user. sessions shorter than a minimum length don't count.
+//
+// in classic mode, no break is earned. instead, each completed focus session
+// starts a fixed short break (or long break, once a clover is completed), and
+// the counter waits for the user once the break is over.
+//
+// interruptions of a focus session are counted until the session ends.
+//
+// optionally, a break can start as soon as a focus session is complete, and
+// focus can start again as soon as a break is over, instead of waiting for the
+// user.
+
+const DEFAULT_FOCUS_TIME: u32 = 25 * 60;
+const DEFAULT_CLOVER_BONUS: u32 = 15 * 60;
+const DEFAULT_BREAK_RATIO: f64 = 5.0;
+const DEFAULT_CLOVER_LENGTH: u8 = 4;
+const DEFAULT_BREAK_WARNINGS: [u32; 1] = [30];
+const DEFAULT_FOCUS_WARNINGS: [u32; 1] = [5 * 60];
+const DEFAULT_FLOW_MIN: u32 = 10 * 60;
+const DEFAULT_SHORT_BREAK: u32 = 5 * 60;
+const DEFAULT_LONG_BREAK: u32 = 15 * 60;
 
-const DEFAULT_FOCUS_TIME: u16 = 25 * 60;
-const DEFAULT_CLOVER_BONUS: u16 = 15 * 60;
+/// Config key under which the latest [`CounterSnapshot`] is stored.
+pub const SNAPSHOT_KEY: &str = "counter";
 
-#[derive(Derivative, Copy, Clone)]
-#[derivative(Default)]
+#[derive(Derivative, Clone)]
+#[derivative(Default, PartialEq)]
 pub struct Counter {
+	mode: CounterMode,
 	#[derivative(Default(value = "DEFAULT_FOCUS_TIME"))]
-	original_focus_time: u16,
+	original_focus_time: u32,
+	/// Focus remaining in the session, or focus so far in flowtime mode
 	#[derivative(Default(value = "DEFAULT_FOCUS_TIME"))]
-	focus_time: u16,
+	focus_time: u32,
+	/// Minimum length of a flowtime session for it to count as a pom
+	#[derivative(Default(value = "DEFAULT_FLOW_MIN"))]
+	flow_min: u32,
 	#[derivative(Default(value = "DEFAULT_CLOVER_BONUS"))]
-	clover_break_bonus: u16,
-	break_time: u16,
+	clover_break_bonus: u32,
+	/// Length of the break after a focus session in classic mode
+	#[derivative(Default(value = "DEFAULT_SHORT_BREAK"))]
+	short_break: u32,
+	/// Length of the break after a clover in classic mode
+	#[derivative(Default(value = "DEFAULT_LONG_BREAK"))]
+	long_break: u32,
+	/// Seconds of focus required to earn a second of break
+	#[derivative(Default(value = "DEFAULT_BREAK_RATIO"))]
+	break_ratio: f64,
+	break_time: u32,
+	/// What happens when break runs out during a break
+	break_policy: BreakPolicy,
+	/// Seconds of break taken beyond what was earned, to be repaid from focus
+	overdraft: u32,
+	/// Whether to start a break as soon as a pom is completed
+	auto_break: bool,
+	/// Whether to start focusing as soon as a break is over
+	auto_focus: bool,
+	/// Seconds of break remaining at which to warn that it is almost over
+	#[derivative(Default(value = "DEFAULT_BREAK_WARNINGS.to_vec()"))]
+	break_warnings: Vec<u32>,
+	/// Seconds of focus remaining at which to warn that it is almost over
+	#[derivative(Default(value = "DEFAULT_FOCUS_WARNINGS.to_vec()"))]
+	focus_warnings: Vec<u32>,
 	#[derivative(Default(value = "1"))]
 	pom: u8,
+	/// Number of focus sessions that make up a clover
+	#[derivative(Default(value = "DEFAULT_CLOVER_LENGTH"))]
+	clover_length: u8,
 	work_state: CounterWorkState,
+	/// Wall-clock time at which the current focus session was first started
+	session_start: Option<SystemTime>,
+	/// Interruptions of the current focus session so far
+	interruptions: Interruptions,
+	/// Wall-clock time at which the current break was first started
+	break_start: Option<SystemTime>,
+	/// Seconds of the current break taken so far
+	break_taken: u32,
+	/// Wall-clock time up to which the counter has been advanced
+	#[derivative(PartialEq = "ignore")]
+	last_tick: Option<SystemTime>,
 }
 
 impl Counter {
@@ -33,129 +110,811 @@ impl Counter {
 	}
 
 	pub fn with_opts(opts: &AppOpts) -> Self {
+		let mode = opts.mode.unwrap_or_default();
+		let original_focus_time = opts.focus_time.unwrap_or(DEFAULT_FOCUS_TIME);
 		Counter {
-			original_focus_time: opts.focus_time.unwrap_or(DEFAULT_FOCUS_TIME),
-			focus_time: opts.focus_time.unwrap_or(DEFAULT_FOCUS_TIME),
+			mode,
+			original_focus_time,
+			focus_time: match mode {
+				CounterMode::Flowtime => 0,
+				_ => original_focus_time,
+			},
+			flow_min: opts.flow_min.unwrap_or(DEFAULT_FLOW_MIN),
 			clover_break_bonus: opts
 				.clover_break_bonus
 				.unwrap_or(DEFAULT_CLOVER_BONUS),
+			short_break: opts.short_break.unwrap_or(DEFAULT_SHORT_BREAK),
+			long_break: opts.long_break.unwrap_or(DEFAULT_LONG_BREAK),
+			break_ratio: opts.break_ratio.unwrap_or(DEFAULT_BREAK_RATIO),
+			clover_length: opts.clover_length.unwrap_or(DEFAULT_CLOVER_LENGTH),
+			break_policy: opts.break_policy.unwrap_or_default(),
+			auto_break: opts.auto_break.unwrap_or_default(),
+			auto_focus: opts.auto_focus.unwrap_or_default(),
+			break_warnings: warnings_or(
+				&opts.break_warnings,
+				&DEFAULT_BREAK_WARNINGS,
+			),
+			focus_warnings: warnings_or(
+				&opts.focus_warnings,
+				&DEFAULT_FOCUS_WARNINGS,
+			),
 			..Counter::default()
 		}
 	}
 
-	pub const fn original_focus_time(&self) -> u16 {
+	pub const fn mode(&self) -> CounterMode {
+		self.mode
+	}
+
+	pub const fn original_focus_time(&self) -> u32 {
 		self.original_focus_time
 	}
 
-	pub const fn focus_time(&self) -> u16 {
+	pub const fn focus_time(&self) -> u32 {
 		self.focus_time
 	}
 
-	pub const fn break_time(&self) -> u16 {
+	pub const fn flow_min(&self) -> u32 {
+		self.flow_min
+	}
+
+	/// Seconds of focus so far in the current session.
+	pub const fn focused(&self) -> u32 {
+		match self.mode {
+			CounterMode::Flowtime => self.focus_time,
+			_ => self.original_focus_time - self.focus_time,
+		}
+	}
+
+	pub const fn clover_break_bonus(&self) -> u32 {
+		self.clover_break_bonus
+	}
+
+	pub const fn break_ratio(&self) -> f64 {
+		self.break_ratio
+	}
+
+	pub const fn break_time(&self) -> u32 {
 		self.break_time
 	}
 
+	pub const fn short_break(&self) -> u32 {
+		self.short_break
+	}
+
+	pub const fn long_break(&self) -> u32 {
+		self.long_break
+	}
+
+	/// What happens when break runs out. Classic breaks always wait for the
+	/// user to start the next focus session, unless focus is started
+	/// automatically.
+	pub const fn break_policy(&self) -> BreakPolicy {
+		match (self.mode, self.break_policy) {
+			(CounterMode::Classic, _) | (_, BreakPolicy::Pause)
+				if self.auto_focus =>
+			{
+				BreakPolicy::Resume
+			}
+			(CounterMode::Classic, _) => BreakPolicy::Pause,
+			(_, policy) => policy,
+		}
+	}
+
+	pub const fn auto_break(&self) -> bool {
+		self.auto_break
+	}
+
+	pub const fn auto_focus(&self) -> bool {
+		self.auto_focus
+	}
+
+	pub const fn overdraft(&self) -> u32 {
+		self.overdraft
+	}
+
+	pub fn break_warnings(&self) -> &[u32] {
+		&self.break_warnings
+	}
+
+	pub fn focus_warnings(&self) -> &[u32] {
+		&self.focus_warnings
+	}
+
 	pub const fn pom(&self) -> u8 {
 		self.pom
 	}
 
+	pub const fn clover_length(&self) -> u8 {
+		self.clover_length
+	}
+
 	pub const fn work_state(&self) -> CounterWorkState {
 		self.work_state
 	}
 
-	pub const fn work_state_mut(&mut self) -> &mut CounterWorkState {
-		&mut self.work_state
+	pub const fn session_start(&self) -> Option<SystemTime> {
+		self.session_start
+	}
+
+	pub const fn interruptions(&self) -> Interruptions {
+		self.interruptions
+	}
+
+	/// Applies the settings in `opts`, keeping the progress made so far. Focus
+	/// already done in the current session still counts if the mode is
+	/// changed, or if the session is made shorter, in which case at least a
+	/// second is left for it to end on.
+	pub fn reconfigure(&mut self, opts: &AppOpts) {
+		let focused = self.focused();
+		let configured = Counter::with_opts(opts);
+		*self = Counter {
+			focus_time: configured.focus_time_after(focused),
+			break_time: self.break_time,
+			overdraft: self.overdraft,
+			pom: self.pom.clamp(1, configured.clover_length),
+			work_state: self.work_state,
+			session_start: self.session_start,
+			interruptions: self.interruptions,
+			break_start: self.break_start,
+			break_taken: self.break_taken,
+			last_tick: self.last_tick,
+			..configured
+		};
+	}
+
+	/// The focus time of the counter once `focused` seconds of the session
+	/// have been focused, counting up or down as its mode does.
+	fn focus_time_after(&self, focused: u32) -> u32 {
+		match self.mode {
+			CounterMode::Flowtime => focused,
+			_ => self
+				.original_focus_time
+				.saturating_sub(focused)
+				.max(1)
+				.min(self.original_focus_time),
+		}
+	}
+
+	/// Captures the progress of the counter as of `at`, for persisting
+	/// between runs.
+	pub fn snapshot(&self, at: SystemTime) -> CounterSnapshot {
+		CounterSnapshot {
+			mode: Some(self.mode),
+			focus_time: self.focus_time,
+			break_time: self.break_time,
+			overdraft: self.overdraft,
+			pom: self.pom,
+			work_state: self.work_state,
+			session_start: self.session_start.map(secs_since_epoch),
+			interruptions: self.interruptions,
+			break_start: self.break_start.map(secs_since_epoch),
+			break_taken: self.break_taken,
+			timestamp: secs_since_epoch(at),
+		}
+	}
+
+	/// Restores progress from a snapshot taken in a previous run.
+	///
+	/// Time spent away cannot be counted as focus, so a focusing counter is
+	/// restored paused. A break, however, keeps draining while away, and may
+	/// expire (resetting the session) if the app was closed for long enough,
+	/// emitting the events of catching up like [`Counter::tick`].
+	pub fn restore<F: FnMut(CounterEvent)>(
+		&mut self,
+		snapshot: CounterSnapshot,
+		now: SystemTime,
+		on_event: F,
+	) {
+		// the mode may have changed since, in which case the focus time saved
+		// counts the other way, so it is converted like when reconfigured
+		let focused = match snapshot.mode.unwrap_or(self.mode) {
+			CounterMode::Flowtime => snapshot.focus_time,
+			_ => self.original_focus_time.saturating_sub(snapshot.focus_time),
+		};
+		self.focus_time = self.focus_time_after(focused);
+		self.break_time = snapshot.break_time;
+		self.overdraft = snapshot.overdraft;
+		self.pom = snapshot.pom.clamp(1, self.clover_length);
+		self.work_state = snapshot.work_state;
+		self.session_start = snapshot
+			.session_start
+			.map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+		self.interruptions = snapshot.interruptions;
+		self.break_start = snapshot
+			.break_start
+			.map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+		self.break_taken = snapshot.break_taken;
+
+		if self.work_state.is_focusing() {
+			self.work_state =
+				CounterWorkState::Paused { previous: Phase::Focus };
+			self.last_tick = None;
+		} else {
+			self.last_tick =
+				Some(UNIX_EPOCH + Duration::from_secs(snapshot.timestamp));
+			self.tick_at(now, on_event);
+		}
+	}
+
+	pub fn start(&mut self) -> Option<Transition> {
+		self.transition(CounterWorkState::Focusing)
+	}
+
+	pub fn reset(&mut self) -> Option<Transition> {
+		self.focus_time = match self.mode {
+			CounterMode::Flowtime => 0,
+			_ => self.original_focus_time,
+		};
+		self.session_start = None;
+		self.interruptions = Interruptions::default();
+		self.break_start = None;
+		self.break_taken = 0;
+		self.transition(CounterWorkState::Idle)
+	}
+
+	/// Abandons the current focus session, e.g. after being derailed. Progress
+	/// towards the pom is discarded, but break already earned is kept.
+	pub fn abandon<F: FnMut(CounterEvent)>(&mut self, mut on_event: F) {
+		if self.work_state.phase() != Some(Phase::Focus) {
+			return;
+		}
+
+		on_event(self.end_phase(Phase::Focus, PhaseOutcome::Interrupted));
+		if let Some(transition) = self.reset() {
+			on_event(CounterEvent::Transition(transition));
+		}
+	}
+
+	/// Counts an interruption of the current focus session, returning the
+	/// time at which it happened.
+	pub fn interrupt(&mut self, kind: InterruptionKind) -> Option<SystemTime> {
+		if self.work_state.phase() != Some(Phase::Focus) {
+			return None;
+		}
+
+		match kind {
+			InterruptionKind::Internal => self.interruptions.internal += 1,
+			InterruptionKind::External => self.interruptions.external += 1,
+		}
+		self.last_tick
+	}
+
+	/// Ends the current break early and goes back to focusing. A classic break
+	/// is forfeited, while earned break stays banked.
+	pub fn skip_break<F: FnMut(CounterEvent)>(&mut self, mut on_event: F) {
+		if self.work_state.phase() != Some(Phase::Break) {
+			return;
+		}
+
+		on_event(self.end_phase(Phase::Break, self.break_outcome()));
+		if self.mode == CounterMode::Classic {
+			self.break_time = 0;
+		}
+		if let Some(transition) = self.transition(CounterWorkState::Focusing) {
+			on_event(CounterEvent::Transition(transition));
+		}
+	}
+
+	/// Rewinds progress to that of an `earlier` copy of the counter, e.g. to
+	/// undo an action. Time that has already been counted is not recounted.
+	pub fn rewind(&mut self, earlier: Counter) -> Option<Transition> {
+		let from = self.work_state;
+		*self = Counter { last_tick: self.last_tick, ..earlier };
+		(from != self.work_state)
+			.then_some(Transition { from, to: self.work_state })
+	}
+
+	/// Ends the current flowtime session, counting it as a pom if it was long
+	/// enough. Sessions in other modes end by themselves.
+	pub fn end_session<F: FnMut(CounterEvent)>(&mut self, mut on_event: F) {
+		if self.mode != CounterMode::Flowtime
+			|| self.work_state.phase() != Some(Phase::Focus)
+		{
+			return;
+		}
+
+		let from = self.work_state;
+		if self.focused() >= self.flow_min {
+			self.complete_pom(&mut on_event);
+		} else {
+			on_event(self.end_phase(Phase::Focus, PhaseOutcome::Interrupted));
+			self.reset();
+		}
+		on_event(CounterEvent::Transition(Transition {
+			from,
+			to: self.work_state,
+		}));
+	}
+
+	pub fn toggle_active(&mut self) -> Option<Transition> {
+		let mut to = self.work_state;
+		to.toggle_active();
+		self.transition(to)
+	}
+
+	/// Switches between focusing and taking a break. Going back to focusing
+	/// ends the break.
+	pub fn toggle_break<F: FnMut(CounterEvent)>(&mut self, mut on_event: F) {
+		let mut to = self.work_state;
+		to.toggle_break();
+		if self.work_state.is_on_break() && to != self.work_state {
+			on_event(self.end_phase(Phase::Break, self.break_outcome()));
+		}
+		if let Some(transition) = self.transition(to) {
+			on_event(CounterEvent::Transition(transition));
+		}
+	}
+
+	fn transition(&mut self, to: CounterWorkState) -> Option<Transition> {
+		if to.is_focusing() && self.session_start.is_none() {
+			self.session_start = self.last_tick;
+		}
+		if to.is_on_break() && self.break_start.is_none() {
+			self.break_start = self.last_tick;
+		}
+		let from = std::mem::replace(&mut self.work_state, to);
+		(from != to).then_some(Transition { from, to })
 	}
 
-	pub fn start(&mut self) {
-		self.work_state = CounterWorkState::from(Some(true));
+	/// Advances the counter by the time elapsed on `clock` since the last
+	/// tick.
+	pub fn tick<C: Clock, F: FnMut(CounterEvent)>(
+		&mut self,
+		clock: &C,
+		on_event: F,
+	) {
+		self.tick_at(clock.now(), on_event)
 	}
 
-	pub fn reset(&mut self) {
-		self.focus_time = self.original_focus_time;
-		self.work_state.0.take();
+	/// Advances the counter by the whole seconds elapsed between the last tick
+	/// and `now`, emitting any events that fell due in between (e.g. after
+	/// the system was suspended). Time spent paused is not counted.
+	pub fn tick_at<F: FnMut(CounterEvent)>(
+		&mut self,
+		now: SystemTime,
+		mut on_event: F,
+	) {
+		let last_tick = *self.last_tick.get_or_insert(now);
+		let Ok(elapsed) = now.duration_since(last_tick) else {
+			// the clock went backwards, so count from the new time instead
+			self.last_tick = Some(now);
+			return;
+		};
+
+		let secs = elapsed.as_secs();
+		for step in 1..=secs {
+			if !self.work_state.is_active() {
+				break;
+			}
+			// anything that happens during the step happens as it ends
+			self.last_tick = Some(last_tick + Duration::from_secs(step));
+			self.work(&mut on_event);
+		}
+		// keep the sub-second remainder for the next tick
+		self.last_tick = Some(last_tick + Duration::from_secs(secs));
 	}
 
-	pub fn work<F: Fn(AppNotification)>(&mut self, notifier: F) {
+	/// Advances the counter by a single second.
+	pub fn work<F: FnMut(CounterEvent)>(&mut self, mut on_event: F) {
+		let from = self.work_state;
+
 		if !self.work_state.is_active() {
 			return;
 		} else if self.work_state.is_focusing() {
-			if self.focus_time % 5 == 0 {
-				self.break_time += 1;
+			let focused = self.focused();
+			if self.mode == CounterMode::Flowtime {
+				self.focus_time += 1;
+			} else {
+				self.focus_time -= 1;
+				if self.focus_time == self.original_focus_time / 2 {
+					on_event(CounterEvent::Notify(
+						AppNotification::FocusHalfway,
+					));
+				}
+				if self.focus_warnings.contains(&self.focus_time) {
+					on_event(CounterEvent::Notify(
+						AppNotification::FocusAlmostOver(self.focus_time),
+					));
+				}
 			}
-			self.focus_time -= 1;
-		} else {
+			let earned = match self.mode {
+				CounterMode::Classic => 0,
+				_ => {
+					self.earned_break(focused + 1) - self.earned_break(focused)
+				}
+			};
+			let repaid = earned.min(self.overdraft);
+			self.overdraft -= repaid;
+			self.break_time = self.break_time.saturating_add(earned - repaid);
+		} else if self.break_time > 0 {
 			self.break_time -= 1;
-			if self.break_time == 30 {
-				notifier(AppNotification::BreakAlmostOver);
+			self.break_taken += 1;
+			if self.break_warnings.contains(&self.break_time) {
+				on_event(CounterEvent::Notify(
+					AppNotification::BreakAlmostOver(self.break_time),
+				));
 			} else if self.break_time == 0 {
-				self.reset();
-				notifier(AppNotification::BreakOver);
+				self.end_break(&mut on_event);
 			}
+		} else if self.break_policy() == BreakPolicy::Overdraft {
+			self.overdraft += 1;
+			self.break_taken += 1;
+		} else {
+			// the break was started without any break to spend
+			self.end_break(&mut on_event);
 		}
 
-		if self.focus_time == 0 {
-			self.pom += 1;
-			self.reset();
-			notifier(AppNotification::PomComplete);
+		if self.focus_time == 0 && self.mode != CounterMode::Flowtime {
+			self.complete_pom(&mut on_event);
 		}
 
-		if self.pom == 5 {
-			self.break_time += self.clover_break_bonus;
+		if self.work_state != from {
+			on_event(CounterEvent::Transition(Transition {
+				from,
+				to: self.work_state,
+			}));
+		}
+	}
+
+	fn complete_pom<F: FnMut(CounterEvent)>(&mut self, on_event: &mut F) {
+		on_event(self.end_phase(Phase::Focus, PhaseOutcome::Completed));
+		self.pom += 1;
+		self.reset();
+		on_event(CounterEvent::Notify(AppNotification::PomComplete));
+
+		let clover_complete = self.pom > self.clover_length;
+		if clover_complete {
 			self.pom = 1;
-			notifier(AppNotification::CloverComplete);
+		}
+		match self.mode {
+			CounterMode::Classic => {
+				self.break_time = if clover_complete {
+					self.long_break
+				} else {
+					self.short_break
+				};
+				self.transition(CounterWorkState::OnBreak);
+			}
+			_ if clover_complete => {
+				self.break_time =
+					self.break_time.saturating_add(self.clover_break_bonus)
+			}
+			_ => (),
+		}
+		if self.auto_break && self.break_time > 0 {
+			self.transition(CounterWorkState::OnBreak);
+		}
+		if clover_complete {
+			on_event(CounterEvent::Notify(AppNotification::CloverComplete));
 		}
 	}
+
+	fn end_break<F: FnMut(CounterEvent)>(&mut self, on_event: &mut F) {
+		if self.break_policy() != BreakPolicy::Overdraft {
+			on_event(self.end_phase(Phase::Break, PhaseOutcome::Completed));
+		}
+		match self.break_policy() {
+			BreakPolicy::Reset => {
+				if self.session_start.is_some() {
+					on_event(
+						self.end_phase(Phase::Focus, PhaseOutcome::Interrupted),
+					);
+				}
+				self.reset();
+				if self.auto_focus {
+					self.start();
+				}
+			}
+			BreakPolicy::Resume => {
+				self.transition(CounterWorkState::Focusing);
+			}
+			// keep counting the break, into the overdraft
+			BreakPolicy::Overdraft => (),
+			BreakPolicy::Pause => {
+				self.transition(CounterWorkState::Paused {
+					previous: Phase::Focus,
+				});
+			}
+		}
+		on_event(CounterEvent::Notify(AppNotification::BreakOver(
+			self.break_policy(),
+		)));
+	}
+
+	/// Sums up the current `phase` as having ended with `outcome`, as of the
+	/// last tick.
+	fn end_phase(
+		&mut self,
+		phase: Phase,
+		outcome: PhaseOutcome,
+	) -> CounterEvent {
+		let (started, counted, interruptions) = match phase {
+			Phase::Focus => {
+				(self.session_start, self.focused(), self.interruptions)
+			}
+			Phase::Break => (
+				self.break_start.take(),
+				std::mem::take(&mut self.break_taken),
+				Interruptions::default(),
+			),
+		};
+		CounterEvent::PhaseEnded(PhaseSummary {
+			phase,
+			outcome,
+			started,
+			ended: self.last_tick,
+			counted,
+			interruptions,
+			clover: phase == Phase::Focus
+				&& outcome == PhaseOutcome::Completed
+				&& self.pom >= self.clover_length,
+		})
+	}
+
+	/// How the current break ends if the user ends it now.
+	fn break_outcome(&self) -> PhaseOutcome {
+		match self.break_time {
+			0 => PhaseOutcome::Completed,
+			_ => PhaseOutcome::Skipped,
+		}
+	}
+
+	/// Total break earned by `focused` seconds of a focus session.
+	fn earned_break(&self, focused: u32) -> u32 {
+		(focused as f64 / self.break_ratio).floor() as u32
+	}
 }
 
-#[derive(Copy, Clone, Default)]
-pub struct CounterWorkState(Option<bool>);
+/// Warning thresholds from `opts`, or `default` if none were given. A zero
+/// threshold is dropped, as the end of the phase has a notification of its own.
+fn warnings_or(opts: &Option<Vec<u32>>, default: &[u32]) -> Vec<u32> {
+	let mut warnings = opts.as_deref().unwrap_or(default).to_vec();
+	warnings.retain(|&secs| secs > 0);
+	warnings
+}
+
+fn secs_since_epoch(time: SystemTime) -> u64 {
+	time.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs())
+}
+
+/// The persisted progress of a [`Counter`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CounterSnapshot {
+	/// Mode the counter was in, unknown for snapshots saved before it was
+	pub mode: Option<CounterMode>,
+	pub focus_time: u32,
+	pub break_time: u32,
+	pub overdraft: u32,
+	pub pom: u8,
+	pub work_state: CounterWorkState,
+	/// Seconds since the UNIX epoch at which the focus session was started
+	pub session_start: Option<u64>,
+	pub interruptions: Interruptions,
+	/// Seconds since the UNIX epoch at which the break was started
+	pub break_start: Option<u64>,
+	/// Seconds of the break taken so far
+	pub break_taken: u32,
+	/// Seconds since the UNIX epoch at which the snapshot was taken
+	pub timestamp: u64,
+}
+
+/// Where an interruption of a focus session came from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InterruptionKind {
+	/// The urge to do something else, e.g. check messages
+	Internal,
+	/// Someone or something else, e.g. a phone call
+	External,
+}
+
+impl Display for InterruptionKind {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match self {
+			InterruptionKind::Internal => write!(f, "internal"),
+			InterruptionKind::External => write!(f, "external"),
+		}
+	}
+}
+
+/// Numbers of interruptions of a focus session, by kind.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Interruptions {
+	pub internal: u16,
+	pub external: u16,
+}
+
+/// How focus sessions are timed.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum CounterMode {
+	/// Count focus down from a fixed length, earning break along the way
+	#[default]
+	Earned,
+	/// Count focus up with no fixed end, earning break along the way
+	Flowtime,
+	/// Count focus down from a fixed length, followed by a fixed break
+	Classic,
+}
+
+impl Display for CounterMode {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match self {
+			CounterMode::Earned => write!(f, "earned"),
+			CounterMode::Flowtime => write!(f, "flowtime"),
+			CounterMode::Classic => write!(f, "classic"),
+		}
+	}
+}
+
+/// What to do when break runs out during a break.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum BreakPolicy {
+	/// Reset the current focus session
+	#[default]
+	Reset,
+	/// Go straight back to focusing
+	Resume,
+	/// Keep the break going, repaying the extra from the next focus
+	Overdraft,
+	/// Pause until focus is resumed
+	Pause,
+}
+
+impl Display for BreakPolicy {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match self {
+			BreakPolicy::Reset => write!(f, "reset"),
+			BreakPolicy::Resume => write!(f, "resume"),
+			BreakPolicy::Overdraft => write!(f, "overdraft"),
+			BreakPolicy::Pause => write!(f, "pause"),
+		}
+	}
+}
+
+/// Something that happened while the counter was working.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CounterEvent {
+	Notify(AppNotification),
+	Transition(Transition),
+	PhaseEnded(PhaseSummary),
+}
+
+/// A focus session or break that has come to an end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PhaseSummary {
+	pub phase: Phase,
+	pub outcome: PhaseOutcome,
+	/// Wall-clock time at which the phase was first started, if known
+	pub started: Option<SystemTime>,
+	/// Wall-clock time at which the phase ended, if known
+	pub ended: Option<SystemTime>,
+	/// Seconds counted towards the phase, which leaves out time spent paused
+	/// (and on break, for a focus session)
+	pub counted: u32,
+	/// Interruptions of the phase, if it was a focus session
+	pub interruptions: Interruptions,
+	/// Whether the phase was the focus session that completed a clover
+	pub clover: bool,
+}
+
+/// How a focus session or break came to an end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PhaseOutcome {
+	/// Ran its full course, or all of the break was spent
+	Completed,
+	/// Abandoned before completion
+	Interrupted,
+	/// Ended early by the user
+	Skipped,
+}
+
+/// A change in the [`CounterWorkState`] of a counter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Transition {
+	pub from: CounterWorkState,
+	pub to: CounterWorkState,
+}
+
+/// The part of a session that time is being counted towards.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Phase {
+	Focus,
+	Break,
+}
+
+/// The state of a counter.
+///
+/// ```text
+/// Idle --start--> Focusing <--toggle break--> OnBreak
+///                    |                           |
+///              toggle active               toggle active
+///                    |                           |
+///         Paused { previous: Focus }  Paused { previous: Break }
+/// ```
+///
+/// Toggling active while paused resumes the phase that was paused, and a
+/// reset from any state returns to `Idle`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CounterWorkState {
+	#[default]
+	Idle,
+	Focusing,
+	OnBreak,
+	Paused {
+		previous: Phase,
+	},
+}
 
 impl CounterWorkState {
 	pub fn is_active(&self) -> bool {
-		self.0.is_some()
+		matches!(self, CounterWorkState::Focusing | CounterWorkState::OnBreak)
 	}
 
 	pub fn is_focusing(&self) -> bool {
-		self.is_active() && self.0.unwrap()
+		*self == CounterWorkState::Focusing
 	}
 
-	pub fn into_inner(&self) -> Option<bool> {
-		self.0
+	pub fn is_on_break(&self) -> bool {
+		*self == CounterWorkState::OnBreak
 	}
 
-	pub fn set_active(&mut self, active: bool) {
-		self.0 = if active { Some(true) } else { None }
+	pub fn is_paused(&self) -> bool {
+		matches!(self, CounterWorkState::Paused { .. })
 	}
 
-	pub fn toggle_active(&mut self) {
-		self.0 = match self.0 {
-			None => Some(true),
-			Some(_) => None,
+	/// The phase being counted, or that was being counted before pausing.
+	pub fn phase(&self) -> Option<Phase> {
+		match self {
+			CounterWorkState::Idle => None,
+			CounterWorkState::Focusing => Some(Phase::Focus),
+			CounterWorkState::OnBreak => Some(Phase::Break),
+			CounterWorkState::Paused { previous } => Some(*previous),
 		}
 	}
 
-	pub fn set_break(&mut self, on_break: bool) {
-		self.0 = Some(on_break);
+	pub fn toggle_active(&mut self) {
+		*self = match *self {
+			CounterWorkState::Idle => CounterWorkState::Focusing,
+			CounterWorkState::Focusing => {
+				CounterWorkState::Paused { previous: Phase::Focus }
+			}
+			CounterWorkState::OnBreak => {
+				CounterWorkState::Paused { previous: Phase::Break }
+			}
+			CounterWorkState::Paused { previous } => previous.into(),
+		}
 	}
 
 	pub fn toggle_break(&mut self) {
-		self.0 = self.0.take().map(|state| !state);
+		*self = match *self {
+			CounterWorkState::Focusing => CounterWorkState::OnBreak,
+			CounterWorkState::OnBreak => CounterWorkState::Focusing,
+			x => x,
+		}
 	}
 }
 
-impl From<Option<bool>> for CounterWorkState {
-	fn from(x: Option<bool>) -> Self {
-		CounterWorkState(x)
+impl From<Phase> for CounterWorkState {
+	fn from(phase: Phase) -> Self {
+		match phase {
+			Phase::Focus => CounterWorkState::Focusing,
+			Phase::Break => CounterWorkState::OnBreak,
+		}
 	}
 }
 
-impl ToString for CounterWorkState {
-	fn to_string(&self) -> String {
-		match self.0 {
-			None => "Paused".to_string(),
-			Some(false) => "Break".to_string(),
-			Some(true) => "Focus".to_string(),
+impl Display for CounterWorkState {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match self {
+			CounterWorkState::Idle => write!(f, "Idle"),
+			CounterWorkState::Focusing => write!(f, "Focus"),
+			CounterWorkState::OnBreak => write!(f, "Break"),
+			CounterWorkState::Paused { previous: Phase::Focus } => {
+				write!(f, "Paused (focus)")
+			}
+			CounterWorkState::Paused { previous: Phase::Break } => {
+				write!(f, "Paused (break)")
+			}
 		}
 	}
 }
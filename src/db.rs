@@ -1,6 +1,62 @@
-use diesel::{delete, insert_into, prelude::*, replace_into};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use crate::schema::{config, tasks};
+use clap::ValueEnum;
+use diesel::{delete, insert_into, prelude::*, replace_into, result::Error};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations};
+
+use crate::{
+	counter::{
+		BreakPolicy,
+		CounterMode,
+		CounterSnapshot,
+		CounterWorkState,
+		InterruptionKind,
+		Interruptions,
+		Phase,
+		PhaseOutcome,
+		PhaseSummary,
+	},
+	schema::{config, interruptions, sessions, tasks},
+	ui::Theme,
+};
+
+/// Migrations that bring a database up to date with the schema.
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
+/// Maximum number of characters in the name of a task.
+pub const MAX_TASK_NAME: usize = 16;
+/// Number of quadrants in the Eisenhower matrix.
+pub const QUADRANTS: i32 = 4;
+/// Names of the quadrants of the Eisenhower matrix, by `box_cat`: urgent and
+/// important, important, urgent, then neither.
+pub const QUADRANT_NAMES: [&str; QUADRANTS as usize] =
+	["Do", "Schedule", "Delegate", "Eliminate"];
+
+/// Why an operation on a task failed.
+#[derive(Debug, thiserror::Error)]
+pub enum TaskError {
+	#[error("there is no task with id {0}")]
+	NotFound(i32),
+	#[error("a task name must be 1 to {MAX_TASK_NAME} characters long")]
+	InvalidName,
+	#[error(
+		"a task must require at least one focus session, and no fewer than \
+		 have been done ({focus_done}/{focus_req})"
+	)]
+	InvalidProgress { focus_done: i32, focus_req: i32 },
+	#[error("'{0}' is not a number of focus sessions")]
+	InvalidCount(String),
+	#[error("{0} is not a quadrant of the Eisenhower matrix")]
+	InvalidQuadrant(i32),
+	#[error("{0} is not a column of the Kanban board")]
+	InvalidStatus(i32),
+	#[error("task {0} has already had all of its focus sessions done")]
+	AlreadyComplete(i32),
+	#[error(transparent)]
+	Database(#[from] Error),
+}
+
+pub type TaskResult<T> = Result<T, TaskError>;
 
 #[derive(Clone, Debug, Queryable)]
 #[diesel(table_name = tasks)]
@@ -10,6 +66,20 @@ pub struct Task {
 	pub focus_req: i32,
 	pub focus_done: i32,
 	pub box_cat: i32,
+	/// Column of the Kanban board that the task is in
+	pub status: i32,
+	/// Where the task is placed within its column, lowest first
+	pub position: i32,
+}
+
+impl Task {
+	pub fn is_complete(&self) -> bool {
+		self.focus_done >= self.focus_req
+	}
+
+	pub fn quadrant_name(&self) -> &'static str {
+		QUADRANT_NAMES.get(self.box_cat as usize).copied().unwrap_or("Unknown")
+	}
 }
 
 #[derive(Insertable)]
@@ -19,6 +89,59 @@ pub struct NewTask {
 	pub focus_req: i32,
 	pub focus_done: i32,
 	pub box_cat: i32,
+	pub status: i32,
+	pub position: i32,
+}
+
+impl NewTask {
+	/// Checks the task against the constraints of the `tasks` table, so that
+	/// a violation is reported as a [`TaskError`] rather than a database error.
+	fn validate(&self) -> TaskResult<()> {
+		let name_len = self.name.chars().count();
+		if name_len == 0 || name_len > MAX_TASK_NAME {
+			return Err(TaskError::InvalidName);
+		}
+		if self.focus_req < 1
+			|| self.focus_done < 0
+			|| self.focus_done > self.focus_req
+		{
+			return Err(TaskError::InvalidProgress {
+				focus_done: self.focus_done,
+				focus_req: self.focus_req,
+			});
+		}
+		if !(0..QUADRANTS).contains(&self.box_cat) {
+			return Err(TaskError::InvalidQuadrant(self.box_cat));
+		}
+		if self.status < 0 {
+			return Err(TaskError::InvalidStatus(self.status));
+		}
+		Ok(())
+	}
+}
+
+/// Changes to make to a task, leaving out the fields to keep.
+#[derive(Clone, Debug, Default, AsChangeset)]
+#[diesel(table_name = tasks)]
+pub struct TaskChanges {
+	pub name: Option<String>,
+	pub focus_req: Option<i32>,
+	pub focus_done: Option<i32>,
+	pub box_cat: Option<i32>,
+	pub status: Option<i32>,
+	pub position: Option<i32>,
+}
+
+impl TaskChanges {
+	/// Whether there are no changes, keeping every field.
+	pub fn is_empty(&self) -> bool {
+		self.name.is_none()
+			&& self.focus_req.is_none()
+			&& self.focus_done.is_none()
+			&& self.box_cat.is_none()
+			&& self.status.is_none()
+			&& self.position.is_none()
+	}
 }
 
 pub fn create_task(
@@ -27,13 +150,33 @@ pub fn create_task(
 	focus_req: i32,
 	focus_done: i32,
 	box_cat: i32,
-) -> QueryResult<Task> {
+) -> TaskResult<Task> {
 	use crate::schema::tasks::dsl::tasks;
 
-	insert_into(tasks)
-		.values(NewTask { name, focus_req, focus_done, box_cat })
-		.returning(tasks::all_columns())
-		.get_result(conn)
+	conn.transaction(|conn| {
+		let task = NewTask {
+			name,
+			focus_req,
+			focus_done,
+			box_cat,
+			status: 0,
+			position: next_position(conn)?,
+		};
+		task.validate()?;
+		Ok(insert_into(tasks)
+			.values(task)
+			.returning(tasks::all_columns())
+			.get_result(conn)?)
+	})
+}
+
+/// A position on the Kanban board after those of all of the tasks.
+fn next_position(conn: &mut SqliteConnection) -> QueryResult<i32> {
+	use crate::schema::tasks::dsl::{position, tasks};
+
+	let last: Option<i32> =
+		tasks.select(diesel::dsl::max(position)).get_result(conn)?;
+	Ok(last.map_or(0, |last| last + 1))
 }
 
 pub fn get_tasks(conn: &mut SqliteConnection) -> QueryResult<Vec<Task>> {
@@ -42,13 +185,402 @@ pub fn get_tasks(conn: &mut SqliteConnection) -> QueryResult<Vec<Task>> {
 	tasks.select(tasks::all_columns()).get_results(conn)
 }
 
-pub fn get_task(
+pub fn get_task(conn: &mut SqliteConnection, task_id: i32) -> TaskResult<Task> {
+	use crate::schema::tasks::dsl::{id, tasks};
+
+	tasks
+		.select(tasks::all_columns())
+		.filter(id.eq(task_id))
+		.get_result(conn)
+		.optional()?
+		.ok_or(TaskError::NotFound(task_id))
+}
+
+/// Applies `changes` to a task, returning the updated task.
+pub fn update_task(
 	conn: &mut SqliteConnection,
 	task_id: i32,
-) -> QueryResult<Task> {
-	use crate::schema::tasks::dsl::{id, tasks};
+	changes: TaskChanges,
+) -> TaskResult<Task> {
+	use crate::schema::tasks::dsl::tasks;
+
+	conn.transaction(|conn| {
+		let task = get_task(conn, task_id)?;
+		// there is no statement for setting nothing, nor any need for one
+		if changes.is_empty() {
+			return Ok(task);
+		}
+		NewTask {
+			name: changes.name.clone().unwrap_or(task.name),
+			focus_req: changes.focus_req.unwrap_or(task.focus_req),
+			focus_done: changes.focus_done.unwrap_or(task.focus_done),
+			box_cat: changes.box_cat.unwrap_or(task.box_cat),
+			status: changes.status.unwrap_or(task.status),
+			position: changes.position.unwrap_or(task.position),
+		}
+		.validate()?;
+		Ok(diesel::update(tasks.find(task_id))
+			.set(changes)
+			.returning(tasks::all_columns())
+			.get_result(conn)?)
+	})
+}
+
+pub fn rename_task(
+	conn: &mut SqliteConnection,
+	task_id: i32,
+	name: String,
+) -> TaskResult<Task> {
+	update_task(conn, task_id, TaskChanges {
+		name: Some(name),
+		..Default::default()
+	})
+}
+
+/// Moves a task to quadrant `box_cat` of the Eisenhower matrix.
+pub fn move_task(
+	conn: &mut SqliteConnection,
+	task_id: i32,
+	box_cat: i32,
+) -> TaskResult<Task> {
+	update_task(conn, task_id, TaskChanges {
+		box_cat: Some(box_cat),
+		..Default::default()
+	})
+}
+
+/// Moves a task to the end of column `status` of the Kanban board.
+pub fn set_task_status(
+	conn: &mut SqliteConnection,
+	task_id: i32,
+	status: i32,
+) -> TaskResult<Task> {
+	conn.transaction(|conn| {
+		let position = next_position(conn)?;
+		update_task(conn, task_id, TaskChanges {
+			status: Some(status),
+			position: Some(position),
+			..Default::default()
+		})
+	})
+}
+
+/// Swaps the positions of two tasks on the Kanban board, returning the first
+/// task as updated.
+pub fn swap_tasks(
+	conn: &mut SqliteConnection,
+	task_id: i32,
+	other_id: i32,
+) -> TaskResult<Task> {
+	use crate::schema::tasks::dsl::{position, tasks};
+
+	conn.transaction(|conn| {
+		let task = get_task(conn, task_id)?;
+		let other = get_task(conn, other_id)?;
+		diesel::update(tasks.find(other_id))
+			.set(position.eq(task.position))
+			.execute(conn)?;
+		Ok(diesel::update(tasks.find(task_id))
+			.set(position.eq(other.position))
+			.returning(tasks::all_columns())
+			.get_result(conn)?)
+	})
+}
 
-	tasks.select(tasks::all_columns()).filter(id.eq(task_id)).get_result(conn)
+/// Marks all of the focus sessions a task requires as done.
+pub fn complete_task(
+	conn: &mut SqliteConnection,
+	task_id: i32,
+) -> TaskResult<Task> {
+	use crate::schema::tasks::dsl::{focus_done, focus_req, tasks};
+
+	diesel::update(tasks.find(task_id))
+		.set(focus_done.eq(focus_req))
+		.returning(tasks::all_columns())
+		.get_result(conn)
+		.optional()?
+		.ok_or(TaskError::NotFound(task_id))
+}
+
+/// Counts another focus session as done for a task, returning the updated
+/// task. A task cannot have more sessions done than it requires.
+pub fn increment_focus_done(
+	conn: &mut SqliteConnection,
+	task_id: i32,
+) -> TaskResult<Task> {
+	conn.transaction(|conn| {
+		let task = get_task(conn, task_id)?;
+		if task.is_complete() {
+			return Err(TaskError::AlreadyComplete(task_id));
+		}
+		update_task(conn, task_id, TaskChanges {
+			focus_done: Some(task.focus_done + 1),
+			..Default::default()
+		})
+	})
+}
+
+/// Credits a completed focus `session` to the task it was recorded against,
+/// returning the updated task. Returns `None` if the session is not credited
+/// to a task, including if the task already has all of its sessions done or
+/// has since been deleted.
+pub fn credit_session(
+	conn: &mut SqliteConnection,
+	session: &Session,
+) -> TaskResult<Option<Task>> {
+	let Some(task_id) = session.task_id else {
+		return Ok(None);
+	};
+	if session.phase() != Phase::Focus
+		|| session.outcome() != PhaseOutcome::Completed
+	{
+		return Ok(None);
+	}
+	match increment_focus_done(conn, task_id) {
+		Ok(task) => Ok(Some(task)),
+		Err(TaskError::AlreadyComplete(_) | TaskError::NotFound(_)) => Ok(None),
+		Err(err) => Err(err),
+	}
+}
+
+/// Deletes a task, unlinking any sessions recorded against it.
+pub fn delete_task(
+	conn: &mut SqliteConnection,
+	task_id: i32,
+) -> TaskResult<()> {
+	use crate::schema::{sessions::dsl as sessions, tasks::dsl::tasks};
+
+	conn.transaction(|conn| {
+		diesel::update(
+			sessions::sessions.filter(sessions::task_id.eq(task_id)),
+		)
+		.set(sessions::task_id.eq(None::<i32>))
+		.execute(conn)?;
+		match delete(tasks.find(task_id)).execute(conn)? {
+			0 => Err(TaskError::NotFound(task_id)),
+			_ => Ok(()),
+		}
+	})
+}
+
+#[derive(Clone, Debug, Queryable)]
+#[diesel(table_name = interruptions)]
+pub struct Interruption {
+	pub id: i32,
+	pub session_start: i64,
+	pub at: i64,
+	pub kind: i32,
+	pub note: Option<String>,
+	/// Focus session that was interrupted, once it has been saved
+	pub session_id: Option<i32>,
+}
+
+impl Interruption {
+	pub fn kind(&self) -> InterruptionKind {
+		match self.kind {
+			0 => InterruptionKind::Internal,
+			_ => InterruptionKind::External,
+		}
+	}
+}
+
+#[derive(Clone, Debug, Insertable)]
+#[diesel(table_name = interruptions)]
+pub struct NewInterruption {
+	/// Seconds since the UNIX epoch at which the focus session was started
+	pub session_start: i64,
+	/// Seconds since the UNIX epoch at which the interruption happened
+	pub at: i64,
+	pub kind: i32,
+	pub note: Option<String>,
+	/// Focus session that was interrupted, if it has been saved already
+	pub session_id: Option<i32>,
+}
+
+impl NewInterruption {
+	pub fn new(
+		session_start: SystemTime,
+		at: SystemTime,
+		kind: InterruptionKind,
+	) -> Self {
+		NewInterruption {
+			session_start: secs_since_epoch(session_start),
+			at: secs_since_epoch(at),
+			kind: kind as i32,
+			note: None,
+			session_id: None,
+		}
+	}
+}
+
+/// Saves an interruption, linking it to the focus session it interrupted if
+/// that has been saved already. Otherwise it is linked once the session is
+/// saved by [`create_session`].
+pub fn create_interruption(
+	conn: &mut SqliteConnection,
+	mut interruption: NewInterruption,
+) -> QueryResult<Interruption> {
+	use crate::schema::{
+		interruptions::dsl::interruptions,
+		sessions::dsl::{id, kind, sessions, started},
+	};
+
+	if interruption.session_id.is_none() {
+		interruption.session_id = sessions
+			.select(id)
+			.filter(started.eq(interruption.session_start))
+			.filter(kind.eq(0))
+			.order(id.desc())
+			.first(conn)
+			.optional()?;
+	}
+	insert_into(interruptions)
+		.values(interruption)
+		.returning(interruptions::all_columns())
+		.get_result(conn)
+}
+
+/// Interruptions of the focus session with id `session_id`, oldest first.
+pub fn get_interruptions(
+	conn: &mut SqliteConnection,
+	session_id: i32,
+) -> QueryResult<Vec<Interruption>> {
+	use crate::schema::interruptions::dsl::{self, at, interruptions};
+
+	interruptions
+		.select(interruptions::all_columns())
+		.filter(dsl::session_id.eq(session_id))
+		.order(at)
+		.get_results(conn)
+}
+
+#[derive(Clone, Debug, Queryable)]
+#[diesel(table_name = sessions)]
+pub struct Session {
+	pub id: i32,
+	pub started: i64,
+	pub ended: i64,
+	pub kind: i32,
+	pub outcome: i32,
+	pub task_id: Option<i32>,
+	pub internal_interruptions: i32,
+	pub external_interruptions: i32,
+	pub counted: i32,
+	/// Whether the session completed a clover
+	pub clover: bool,
+}
+
+impl Session {
+	pub fn phase(&self) -> Phase {
+		match self.kind {
+			0 => Phase::Focus,
+			_ => Phase::Break,
+		}
+	}
+
+	pub fn outcome(&self) -> PhaseOutcome {
+		match self.outcome {
+			0 => PhaseOutcome::Completed,
+			1 => PhaseOutcome::Interrupted,
+			_ => PhaseOutcome::Skipped,
+		}
+	}
+
+	/// Seconds between the start and end of the session, including any time
+	/// spent paused. See `counted` for the time actually spent.
+	pub fn duration(&self) -> i64 {
+		self.ended - self.started
+	}
+}
+
+#[derive(Clone, Debug, Insertable)]
+#[diesel(table_name = sessions)]
+pub struct NewSession {
+	/// Seconds since the UNIX epoch at which the session was started
+	pub started: i64,
+	/// Seconds since the UNIX epoch at which the session ended
+	pub ended: i64,
+	pub kind: i32,
+	pub outcome: i32,
+	pub task_id: Option<i32>,
+	pub internal_interruptions: i32,
+	pub external_interruptions: i32,
+	/// Seconds counted towards the session, leaving out time spent paused
+	pub counted: i32,
+	/// Whether the session completed a clover
+	pub clover: bool,
+}
+
+impl NewSession {
+	/// A session from the `summary` of a phase, or `None` if it was not timed.
+	pub fn new(summary: &PhaseSummary, task_id: Option<i32>) -> Option<Self> {
+		let ended = secs_since_epoch(summary.ended?);
+		Some(NewSession {
+			started: summary.started.map_or(ended, secs_since_epoch),
+			ended,
+			kind: match summary.phase {
+				Phase::Focus => 0,
+				Phase::Break => 1,
+			},
+			outcome: match summary.outcome {
+				PhaseOutcome::Completed => 0,
+				PhaseOutcome::Interrupted => 1,
+				PhaseOutcome::Skipped => 2,
+			},
+			task_id,
+			internal_interruptions: summary.interruptions.internal.into(),
+			external_interruptions: summary.interruptions.external.into(),
+			counted: summary.counted.try_into().unwrap_or(i32::MAX),
+			clover: summary.clover,
+		})
+	}
+}
+
+/// Saves a session, linking a focus session to the interruptions of it saved
+/// so far.
+pub fn create_session(
+	conn: &mut SqliteConnection,
+	session: NewSession,
+) -> QueryResult<Session> {
+	use crate::schema::{
+		interruptions::dsl::{interruptions, session_id, session_start},
+		sessions::dsl::sessions,
+	};
+
+	conn.transaction(|conn| {
+		let session: Session = insert_into(sessions)
+			.values(session)
+			.returning(sessions::all_columns())
+			.get_result(conn)?;
+		if session.phase() == Phase::Focus {
+			diesel::update(interruptions)
+				.filter(session_start.eq(session.started))
+				.filter(session_id.is_null())
+				.set(session_id.eq(session.id))
+				.execute(conn)?;
+		}
+		Ok(session)
+	})
+}
+
+/// Sessions started from `from` up to but not including `to`, oldest first.
+pub fn get_sessions(
+	conn: &mut SqliteConnection,
+	from: SystemTime,
+	to: SystemTime,
+) -> QueryResult<Vec<Session>> {
+	use crate::schema::sessions::dsl::{sessions, started};
+
+	sessions
+		.select(sessions::all_columns())
+		.filter(started.ge(secs_since_epoch(from)))
+		.filter(started.lt(secs_since_epoch(to)))
+		.order(started)
+		.get_results(conn)
+}
+
+fn secs_since_epoch(time: SystemTime) -> i64 {
+	time.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs() as i64)
 }
 
 #[derive(Clone, Queryable, Insertable)]
@@ -56,56 +588,363 @@ pub fn get_task(
 pub struct ConfigItem {
 	pub key: String,
 	pub data: Vec<u8>,
+	/// Version of the encoding of `data`, or 0 if it is from before encodings
+	/// were versioned
+	pub version: i32,
 }
 
-pub trait ConfigValue {
+/// Why a config value could not be saved or loaded.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+	#[error("nothing is saved under '{0}'")]
+	NotFound(String),
+	#[error(
+		"'{key}' was saved in version {version} of its encoding, which this \
+		 release does not understand"
+	)]
+	UnsupportedVersion { key: String, version: i32 },
+	#[error("'{key}' could not be decoded: {source}")]
+	Decode { key: String, source: DecodeError },
+	#[error(transparent)]
+	Database(#[from] Error),
+}
+
+pub type ConfigResult<T> = Result<T, ConfigError>;
+
+/// Why the data saved for a config value does not decode to it.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum DecodeError {
+	#[error("expected {expected} bytes, found {found}")]
+	WrongLength { expected: usize, found: usize },
+	#[error("{value} is not a known {kind}")]
+	UnknownVariant { kind: &'static str, value: u8 },
+	#[error("{kind} out of range")]
+	OutOfRange { kind: &'static str },
+	#[error("text is not valid UTF-8")]
+	InvalidUtf8,
+}
+
+/// A value that can be saved in the config table, under a key.
+pub trait ConfigValue: Sized {
+	/// Version of the encoding written by [`ConfigValue::encode`]. Whenever
+	/// the encoding changes this must be bumped, and
+	/// [`ConfigValue::decode`] must go on accepting the earlier versions.
+	const VERSION: i32 = 1;
+
+	fn encode(&self) -> Vec<u8>;
+
+	/// Decodes `data` saved in `version` of the encoding, where version 0 is
+	/// from before encodings were versioned.
+	fn decode(data: &[u8], version: i32) -> Result<Self, DecodeError>;
+
 	fn set_into(
 		self,
 		conn: &mut SqliteConnection,
 		key: String,
-	) -> QueryResult<()>;
-	fn get_from(conn: &mut SqliteConnection, key: String) -> QueryResult<Self>
-	where
-		Self: Sized; // required to use in Result types
-}
+	) -> ConfigResult<()> {
+		set_config_data(conn, ConfigItem {
+			key,
+			data: self.encode(),
+			version: Self::VERSION,
+		})
+	}
 
-impl ConfigValue for u16 {
-	fn set_into(
-		self,
+	fn get_from(
 		conn: &mut SqliteConnection,
 		key: String,
-	) -> QueryResult<()> {
-		set_config_data(conn, key, self.to_be_bytes().to_vec())
+	) -> ConfigResult<Self> {
+		let item = get_config_data(conn, key)?;
+		if !(0..=Self::VERSION).contains(&item.version) {
+			return Err(ConfigError::UnsupportedVersion {
+				key: item.key,
+				version: item.version,
+			});
+		}
+		Self::decode(&item.data, item.version)
+			.map_err(|source| ConfigError::Decode { key: item.key, source })
+	}
+}
+
+/// Takes `data` as exactly `N` bytes.
+fn exact<const N: usize>(data: &[u8]) -> Result<[u8; N], DecodeError> {
+	data.try_into().map_err(|_| DecodeError::WrongLength {
+		expected: N,
+		found: data.len(),
+	})
+}
+
+impl ConfigValue for u8 {
+	fn encode(&self) -> Vec<u8> {
+		vec![*self]
+	}
+
+	fn decode(data: &[u8], _: i32) -> Result<Self, DecodeError> {
+		exact::<1>(data).map(|[x]| x)
+	}
+}
+
+impl ConfigValue for u16 {
+	fn encode(&self) -> Vec<u8> {
+		self.to_be_bytes().to_vec()
+	}
+
+	fn decode(data: &[u8], _: i32) -> Result<Self, DecodeError> {
+		exact(data).map(u16::from_be_bytes)
+	}
+}
+
+impl ConfigValue for u32 {
+	fn encode(&self) -> Vec<u8> {
+		self.to_be_bytes().to_vec()
+	}
+
+	fn decode(data: &[u8], version: i32) -> Result<Self, DecodeError> {
+		match (version, data) {
+			// saved before durations were widened from u16
+			(0, &[a, b]) => Ok(u16::from_be_bytes([a, b]).into()),
+			_ => exact(data).map(u32::from_be_bytes),
+		}
+	}
+}
+
+impl ConfigValue for i64 {
+	fn encode(&self) -> Vec<u8> {
+		self.to_be_bytes().to_vec()
+	}
+
+	fn decode(data: &[u8], _: i32) -> Result<Self, DecodeError> {
+		exact(data).map(i64::from_be_bytes)
+	}
+}
+
+impl ConfigValue for f64 {
+	fn encode(&self) -> Vec<u8> {
+		self.to_be_bytes().to_vec()
 	}
 
-	fn get_from(conn: &mut SqliteConnection, key: String) -> QueryResult<Self> {
-		get_config_data(conn, key)
-			.map(|data| u16::from_be_bytes(data.try_into().unwrap()))
+	fn decode(data: &[u8], _: i32) -> Result<Self, DecodeError> {
+		exact(data).map(f64::from_be_bytes)
 	}
 }
 
 impl ConfigValue for bool {
-	fn set_into(
-		self,
-		conn: &mut SqliteConnection,
-		key: String,
-	) -> QueryResult<()> {
-		set_config_data(conn, key, vec![self as u8])
+	fn encode(&self) -> Vec<u8> {
+		vec![*self as u8]
+	}
+
+	fn decode(data: &[u8], _: i32) -> Result<Self, DecodeError> {
+		match exact::<1>(data)? {
+			[0] => Ok(false),
+			[1] => Ok(true),
+			[value] => Err(DecodeError::UnknownVariant { kind: "bool", value }),
+		}
+	}
+}
+
+impl ConfigValue for String {
+	fn encode(&self) -> Vec<u8> {
+		self.as_bytes().to_vec()
+	}
+
+	fn decode(data: &[u8], _: i32) -> Result<Self, DecodeError> {
+		String::from_utf8(data.to_vec()).map_err(|_| DecodeError::InvalidUtf8)
+	}
+}
+
+impl ConfigValue for Duration {
+	fn encode(&self) -> Vec<u8> {
+		let mut data = self.as_secs().to_be_bytes().to_vec();
+		data.extend(self.subsec_nanos().to_be_bytes());
+		data
+	}
+
+	fn decode(data: &[u8], _: i32) -> Result<Self, DecodeError> {
+		let data: [u8; 12] = exact(data)?;
+		let (secs, nanos) = data.split_at(8);
+		let nanos = u32::from_be_bytes(nanos.try_into().unwrap());
+		if nanos >= 1_000_000_000 {
+			return Err(DecodeError::OutOfRange { kind: "nanoseconds" });
+		}
+		Ok(Duration::new(u64::from_be_bytes(secs.try_into().unwrap()), nanos))
+	}
+}
+
+impl ConfigValue for Vec<u32> {
+	fn encode(&self) -> Vec<u8> {
+		self.iter().flat_map(|x| x.to_be_bytes()).collect()
+	}
+
+	fn decode(data: &[u8], _: i32) -> Result<Self, DecodeError> {
+		if !data.len().is_multiple_of(4) {
+			return Err(DecodeError::WrongLength {
+				expected: data.len() / 4 * 4,
+				found: data.len(),
+			});
+		}
+		Ok(data
+			.chunks_exact(4)
+			.map(|x| u32::from_be_bytes(x.try_into().unwrap()))
+			.collect())
+	}
+}
+
+impl ConfigValue for Vec<String> {
+	fn encode(&self) -> Vec<u8> {
+		// NUL cannot be passed in a command line argument, so it cannot be in
+		// any of the strings
+		self.join("\0").into_bytes()
+	}
+
+	fn decode(data: &[u8], version: i32) -> Result<Self, DecodeError> {
+		// no strings and a single empty one are both saved as nothing, so read
+		// that back as no strings, which is the one of them worth keeping
+		if data.is_empty() {
+			return Ok(Vec::new());
+		}
+		let data = String::decode(data, version)?;
+		Ok(data.split('\0').map(str::to_string).collect())
+	}
+}
+
+/// Encodes the variant of a [`ValueEnum`] as its index.
+fn encode_variant<T: ValueEnum + PartialEq>(value: &T) -> Vec<u8> {
+	let idx = T::value_variants().iter().position(|x| x == value);
+	vec![idx.unwrap_or(0) as u8]
+}
+
+/// Decodes the variant of a [`ValueEnum`] from its index, naming the enum
+/// `kind` in any error.
+fn decode_variant<T: ValueEnum>(
+	data: &[u8],
+	kind: &'static str,
+) -> Result<T, DecodeError> {
+	let [value] = exact::<1>(data)?;
+	T::value_variants()
+		.get(value as usize)
+		.cloned()
+		.ok_or(DecodeError::UnknownVariant { kind, value })
+}
+
+impl ConfigValue for CounterMode {
+	fn encode(&self) -> Vec<u8> {
+		encode_variant(self)
+	}
+
+	fn decode(data: &[u8], _: i32) -> Result<Self, DecodeError> {
+		decode_variant(data, "counter mode")
+	}
+}
+
+impl ConfigValue for BreakPolicy {
+	fn encode(&self) -> Vec<u8> {
+		encode_variant(self)
+	}
+
+	fn decode(data: &[u8], _: i32) -> Result<Self, DecodeError> {
+		decode_variant(data, "break policy")
+	}
+}
+
+impl ConfigValue for Theme {
+	fn encode(&self) -> Vec<u8> {
+		encode_variant(self)
 	}
 
-	fn get_from(conn: &mut SqliteConnection, key: String) -> QueryResult<Self> {
-		get_config_data(conn, key).map(|data| data[0] == 1)
+	fn decode(data: &[u8], _: i32) -> Result<Self, DecodeError> {
+		decode_variant(data, "theme")
+	}
+}
+
+impl ConfigValue for CounterSnapshot {
+	/// The first layout, which snapshots saved before encodings were
+	/// versioned already have, followed by the mode since version 2
+	const VERSION: i32 = 2;
+
+	fn encode(&self) -> Vec<u8> {
+		let mut data = Vec::with_capacity(47);
+		data.extend(self.focus_time.to_be_bytes());
+		data.extend(self.break_time.to_be_bytes());
+		data.extend(self.overdraft.to_be_bytes());
+		data.push(self.pom);
+		data.push(match self.work_state {
+			CounterWorkState::Idle => 0,
+			CounterWorkState::OnBreak => 1,
+			CounterWorkState::Focusing => 2,
+			CounterWorkState::Paused { previous: Phase::Focus } => 3,
+			CounterWorkState::Paused { previous: Phase::Break } => 4,
+		});
+		data.extend(self.timestamp.to_be_bytes());
+		// a session cannot have started at the epoch, so zero means none
+		data.extend(self.session_start.unwrap_or(0).to_be_bytes());
+		data.extend(self.interruptions.internal.to_be_bytes());
+		data.extend(self.interruptions.external.to_be_bytes());
+		data.extend(self.break_start.unwrap_or(0).to_be_bytes());
+		data.extend(self.break_taken.to_be_bytes());
+		// the mode is unknown only for snapshots from before it was saved
+		match &self.mode {
+			Some(mode) => data.extend(encode_variant(mode)),
+			None => data.push(u8::MAX),
+		}
+		data
+	}
+
+	fn decode(data: &[u8], version: i32) -> Result<Self, DecodeError> {
+		let (data, mode) = match version {
+			0 | 1 => (exact::<46>(data)?, None),
+			_ => {
+				let data: [u8; 47] = exact(data)?;
+				let mode = match data[46] {
+					u8::MAX => None,
+					_ => Some(decode_variant(&data[46..], "counter mode")?),
+				};
+				(data[..46].try_into().unwrap(), mode)
+			}
+		};
+		let u16_at = |i: usize| u16::from_be_bytes([data[i], data[i + 1]]);
+		let u32_at =
+			|i: usize| u32::from_be_bytes(data[i..i + 4].try_into().unwrap());
+		let u64_at =
+			|i: usize| u64::from_be_bytes(data[i..i + 8].try_into().unwrap());
+		let work_state = match data[13] {
+			0 => CounterWorkState::Idle,
+			1 => CounterWorkState::OnBreak,
+			2 => CounterWorkState::Focusing,
+			3 => CounterWorkState::Paused { previous: Phase::Focus },
+			4 => CounterWorkState::Paused { previous: Phase::Break },
+			value => {
+				return Err(DecodeError::UnknownVariant {
+					kind: "work state",
+					value,
+				})
+			}
+		};
+
+		Ok(CounterSnapshot {
+			mode,
+			focus_time: u32_at(0),
+			break_time: u32_at(4),
+			overdraft: u32_at(8),
+			pom: data[12],
+			work_state,
+			timestamp: u64_at(14),
+			session_start: Some(u64_at(22)).filter(|&secs| secs != 0),
+			interruptions: Interruptions {
+				internal: u16_at(30),
+				external: u16_at(32),
+			},
+			break_start: Some(u64_at(34)).filter(|&secs| secs != 0),
+			break_taken: u32_at(42),
+		})
 	}
 }
 
 fn set_config_data(
 	conn: &mut SqliteConnection,
-	key: String,
-	data: Vec<u8>,
-) -> QueryResult<()> {
+	item: ConfigItem,
+) -> ConfigResult<()> {
 	use crate::schema::config::dsl::config;
 
-	replace_into(config).values(ConfigItem { key, data }).execute(conn)?;
+	replace_into(config).values(item).execute(conn)?;
 	Ok(())
 }
 
@@ -122,8 +961,13 @@ pub fn unset_config_data(
 fn get_config_data(
 	conn: &mut SqliteConnection,
 	select_key: String,
-) -> QueryResult<Vec<u8>> {
+) -> ConfigResult<ConfigItem> {
 	use crate::schema::config::dsl::*;
 
-	config.select(data).filter(key.eq(select_key)).get_result(conn)
+	config
+		.select(config::all_columns())
+		.filter(key.eq(&select_key))
+		.get_result(conn)
+		.optional()?
+		.ok_or(ConfigError::NotFound(select_key))
 }
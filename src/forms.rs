@@ -0,0 +1,243 @@
+use diesel::{QueryResult, SqliteConnection};
+
+use crate::db::{
+	create_task,
+	get_tasks,
+	update_task,
+	Task,
+	TaskChanges,
+	TaskError,
+	MAX_TASK_NAME,
+	QUADRANTS,
+	QUADRANT_NAMES,
+};
+
+/// A field of a [`TaskForm`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TaskField {
+	#[default]
+	Name,
+	FocusReq,
+	FocusDone,
+	Quadrant,
+}
+
+impl TaskField {
+	pub const ALL: [TaskField; 4] = [
+		TaskField::Name,
+		TaskField::FocusReq,
+		TaskField::FocusDone,
+		TaskField::Quadrant,
+	];
+
+	pub fn label(&self) -> &'static str {
+		match self {
+			TaskField::Name => "Name",
+			TaskField::FocusReq => "Sessions required",
+			TaskField::FocusDone => "Sessions done",
+			TaskField::Quadrant => "Quadrant",
+		}
+	}
+}
+
+/// A form for adding a task, or editing an existing one.
+#[derive(Clone, Debug)]
+pub struct TaskForm {
+	/// Task being edited, or `None` for a new task
+	pub task_id: Option<i32>,
+	pub name: String,
+	pub focus_req: String,
+	pub focus_done: String,
+	pub box_cat: i32,
+	/// Field being edited
+	pub field: TaskField,
+	/// Why the form could not be saved, if it could not
+	pub error: Option<String>,
+}
+
+impl Default for TaskForm {
+	fn default() -> Self {
+		TaskForm {
+			task_id: None,
+			name: String::new(),
+			focus_req: "4".to_string(),
+			focus_done: "0".to_string(),
+			box_cat: 0,
+			field: TaskField::default(),
+			error: None,
+		}
+	}
+}
+
+impl TaskForm {
+	pub fn new() -> Self {
+		TaskForm::default()
+	}
+
+	pub fn edit(task: &Task) -> Self {
+		TaskForm {
+			task_id: Some(task.id),
+			name: task.name.clone(),
+			focus_req: task.focus_req.to_string(),
+			focus_done: task.focus_done.to_string(),
+			box_cat: task.box_cat,
+			..Default::default()
+		}
+	}
+
+	/// The value of `field`, as shown in the form.
+	pub fn value(&self, field: TaskField) -> &str {
+		match field {
+			TaskField::Name => &self.name,
+			TaskField::FocusReq => &self.focus_req,
+			TaskField::FocusDone => &self.focus_done,
+			TaskField::Quadrant => QUADRANT_NAMES[self.box_cat as usize],
+		}
+	}
+
+	/// The text being edited, unless the field is chosen from a set of values.
+	pub fn input(&mut self) -> Option<&mut String> {
+		match self.field {
+			TaskField::Name => Some(&mut self.name),
+			TaskField::FocusReq => Some(&mut self.focus_req),
+			TaskField::FocusDone => Some(&mut self.focus_done),
+			TaskField::Quadrant => None,
+		}
+	}
+
+	/// Moves to the field `by` fields after the current one, wrapping around.
+	pub fn move_field(&mut self, by: isize) {
+		let idx = TaskField::ALL.iter().position(|&x| x == self.field);
+		let len = TaskField::ALL.len() as isize;
+		let idx = (idx.unwrap_or(0) as isize + by).rem_euclid(len);
+		self.field = TaskField::ALL[idx as usize];
+	}
+
+	/// Cycles the quadrant `by` quadrants along, if it is being edited.
+	pub fn cycle_quadrant(&mut self, by: i32) {
+		if self.field == TaskField::Quadrant {
+			self.box_cat = (self.box_cat + by).rem_euclid(QUADRANTS);
+		}
+	}
+
+	/// Creates or updates the task from the form.
+	pub fn save(&self, conn: &mut SqliteConnection) -> Result<Task, TaskError> {
+		let name = self.name.trim().to_string();
+		let focus_req = parse_count(&self.focus_req)?;
+		let focus_done = parse_count(&self.focus_done)?;
+		match self.task_id {
+			Some(task_id) => update_task(conn, task_id, TaskChanges {
+				name: Some(name),
+				focus_req: Some(focus_req),
+				focus_done: Some(focus_done),
+				box_cat: Some(self.box_cat),
+				..Default::default()
+			}),
+			None => {
+				create_task(conn, name, focus_req, focus_done, self.box_cat)
+			}
+		}
+	}
+}
+
+fn parse_count(input: &str) -> Result<i32, TaskError> {
+	input
+		.trim()
+		.parse()
+		.map_err(|_| TaskError::InvalidCount(input.trim().to_string()))
+}
+
+/// An overlay for picking a task to work on by typing part of its name.
+#[derive(Clone, Debug, Default)]
+pub struct TaskPicker {
+	pub query: String,
+	/// Row selected among the matches
+	pub selected: usize,
+	tasks: Vec<Task>,
+}
+
+impl TaskPicker {
+	pub fn new(tasks: Vec<Task>) -> Self {
+		TaskPicker { tasks, ..Default::default() }
+	}
+
+	/// A picker over the tasks in the database.
+	pub fn load(conn: &mut SqliteConnection) -> QueryResult<Self> {
+		get_tasks(conn).map(TaskPicker::new)
+	}
+
+	/// Tasks whose names match the query, best match first.
+	pub fn matches(&self) -> Vec<&Task> {
+		let mut matches: Vec<(u32, &Task)> = self
+			.tasks
+			.iter()
+			.filter_map(|task| {
+				Some((fuzzy_score(&self.query, &task.name)?, task))
+			})
+			.collect();
+		// stable, so equally good matches stay in the order they were listed
+		matches.sort_by(|(a, _), (b, _)| b.cmp(a));
+		matches.into_iter().map(|(_, task)| task).collect()
+	}
+
+	/// The task under the cursor, if any match.
+	pub fn picked(&self) -> Option<&Task> {
+		self.matches().get(self.selected).copied()
+	}
+
+	/// Moves the cursor by `by` rows, staying within the matches.
+	pub fn select_by(&mut self, by: isize) {
+		let row = self.selected.saturating_add_signed(by);
+		self.selected = row.min(self.matches().len().saturating_sub(1));
+	}
+
+	/// Types `c` into the query, going back to the best match.
+	pub fn push(&mut self, c: char) {
+		if self.query.chars().count() < MAX_TASK_NAME {
+			self.query.push(c);
+			self.selected = 0;
+		}
+	}
+
+	/// Erases the last character of the query, going back to the best match.
+	pub fn pop(&mut self) {
+		self.query.pop();
+		self.selected = 0;
+	}
+}
+
+/// How well `query` matches `text`, higher being better, or `None` if the
+/// characters of `query` do not all appear in `text` in order. Case is
+/// ignored, and characters that run on from the last one matched or start a
+/// word count for more.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+	let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+	let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+	let bonus = |j: usize| match j.checked_sub(1) {
+		Some(prev) if text[prev].is_alphanumeric() => 0,
+		_ => 3,
+	};
+
+	// best score for the query so far by where its last character was
+	// matched, one past its index in the text, or 0 while nothing is matched
+	let mut best: Vec<Option<u32>> = vec![None; text.len() + 1];
+	best[0] = Some(0);
+	for &wanted in &query {
+		let mut next = vec![None; text.len() + 1];
+		// best score with the last character matched before `j - 1`
+		let mut earlier = None;
+		for j in 0..text.len() {
+			let run_on = match j {
+				0 => best[0],
+				_ => best[j].map(|score| score + 2),
+			};
+			if text[j] == wanted {
+				next[j + 1] =
+					earlier.max(run_on).map(|score| score + 1 + bonus(j));
+			}
+			earlier = earlier.max(best[j]);
+		}
+		best = next;
+	}
+	best.into_iter().max().flatten()
+}
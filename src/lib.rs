@@ -2,10 +2,13 @@
 extern crate diesel;
 
 pub mod app;
+pub mod clock;
 pub mod counter;
 pub mod db;
-// pub mod eisenhower_mat;
+pub mod forms;
 pub mod schema;
+pub mod settings;
+pub mod stats;
 pub mod terminal;
 mod ui;
 
@@ -18,9 +21,46 @@ mod keys {
 	pub const SIGINT: KeyPair = (KeyModifiers::CONTROL, KeyCode::Char('c'));
 	pub const PAUSE: KeyPair = (KeyModifiers::NONE, KeyCode::Char('p'));
 	pub const BREAK: KeyPair = (KeyModifiers::NONE, KeyCode::Char('b'));
+	pub const END: KeyPair = (KeyModifiers::NONE, KeyCode::Char('e'));
+	pub const ABANDON: KeyPair = (KeyModifiers::NONE, KeyCode::Char('a'));
+	pub const SKIP: KeyPair = (KeyModifiers::NONE, KeyCode::Char('s'));
+	pub const UNDO: KeyPair = (KeyModifiers::NONE, KeyCode::Char('u'));
+	pub const INTERRUPT_INTERNAL: KeyPair =
+		(KeyModifiers::NONE, KeyCode::Char('i'));
+	pub const INTERRUPT_EXTERNAL: KeyPair =
+		(KeyModifiers::SHIFT, KeyCode::Char('I'));
 	pub const HELP: KeyPair = (KeyModifiers::NONE, KeyCode::Char('h'));
 	pub const VI_DOWN: KeyPair = (KeyModifiers::NONE, KeyCode::Char('j'));
 	pub const VI_UP: KeyPair = (KeyModifiers::NONE, KeyCode::Char('k'));
 	pub const PREV: KeyPair = (KeyModifiers::SHIFT, KeyCode::BackTab);
 	pub const NEXT: KeyPair = (KeyModifiers::NONE, KeyCode::Tab);
+	pub const SUBMIT: KeyPair = (KeyModifiers::NONE, KeyCode::Enter);
+	pub const CANCEL: KeyPair = (KeyModifiers::NONE, KeyCode::Esc);
+	pub const ERASE: KeyPair = (KeyModifiers::NONE, KeyCode::Backspace);
+	pub const UP: KeyPair = (KeyModifiers::NONE, KeyCode::Up);
+	pub const DOWN: KeyPair = (KeyModifiers::NONE, KeyCode::Down);
+	pub const LEFT: KeyPair = (KeyModifiers::NONE, KeyCode::Left);
+	pub const RIGHT: KeyPair = (KeyModifiers::NONE, KeyCode::Right);
+	pub const NEW_TASK: KeyPair = (KeyModifiers::NONE, KeyCode::Char('n'));
+	pub const EDIT_TASK: KeyPair = SUBMIT;
+	pub const DELETE_TASK: KeyPair = (KeyModifiers::NONE, KeyCode::Char('d'));
+	pub const WORK_ON_TASK: KeyPair = (KeyModifiers::NONE, KeyCode::Char('c'));
+	pub const SETTINGS: KeyPair = (KeyModifiers::NONE, KeyCode::Char('o'));
+	pub const PICK_TASK: KeyPair = (KeyModifiers::NONE, KeyCode::Char('/'));
+	pub const PREV_QUADRANT: KeyPair = LEFT;
+	pub const NEXT_QUADRANT: KeyPair = RIGHT;
+	pub const PREV_COLUMN: KeyPair = LEFT;
+	pub const NEXT_COLUMN: KeyPair = RIGHT;
+	pub const MOVE_CARD_LEFT: KeyPair = (KeyModifiers::SHIFT, KeyCode::Left);
+	pub const MOVE_CARD_RIGHT: KeyPair = (KeyModifiers::SHIFT, KeyCode::Right);
+	pub const MOVE_CARD_UP: KeyPair = (KeyModifiers::SHIFT, KeyCode::Char('K'));
+	pub const MOVE_CARD_DOWN: KeyPair =
+		(KeyModifiers::SHIFT, KeyCode::Char('J'));
+	/// Keys to move a task to each quadrant, by `box_cat`
+	pub const TO_QUADRANT: [KeyPair; 4] = [
+		(KeyModifiers::NONE, KeyCode::Char('1')),
+		(KeyModifiers::NONE, KeyCode::Char('2')),
+		(KeyModifiers::NONE, KeyCode::Char('3')),
+		(KeyModifiers::NONE, KeyCode::Char('4')),
+	];
 }
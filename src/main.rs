@@ -3,24 +3,32 @@ use std::{
 	time::Duration,
 };
 
+use chrono::Local;
 use clap::Parser;
 use crossterm::event::Event;
 use diesel::{Connection, SqliteConnection};
-use diesel_migrations::{
-	embed_migrations,
-	EmbeddedMigrations,
-	MigrationHarness,
-};
+use diesel_migrations::MigrationHarness;
 use dirs::config_dir;
 use flussomodoro::{
-	app::{App, AppOpts},
+	app::{App, AppOpts, ENV_PREFIX},
+	clock::{Clock, SystemClock},
+	counter::{Counter, CounterSnapshot, SNAPSHOT_KEY},
+	db::{
+		create_interruption,
+		create_session,
+		credit_session,
+		ConfigError,
+		ConfigValue,
+		MIGRATIONS,
+	},
+	stats::Stats,
 	terminal::Terminal,
 };
 use futures::{FutureExt, StreamExt};
-use notify_rust::Notification;
-use tokio::{io, time::interval};
-
-const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+use tokio::{
+	io,
+	time::{interval, MissedTickBehavior},
+};
 
 #[tokio::main]
 async fn main() -> Result<(), io::Error> {
@@ -38,9 +46,29 @@ async fn main() -> Result<(), io::Error> {
 	conn.run_pending_migrations(MIGRATIONS).expect("Failed to run migrations");
 
 	let mut terminal = Terminal::with_stdout(std::io::stdout());
-	let opts = AppOpts::parse();
+	let mut opts = AppOpts::parse();
+	let env = AppOpts::from_env().unwrap_or_else(|err| {
+		eprintln!("In the {ENV_PREFIX}* environment variables:");
+		err.exit()
+	});
+	opts.load_config(&mut conn, &env).expect("Failed to load settings");
+	let clock = SystemClock;
 	let mut app = App::with_opts(&opts);
+	match CounterSnapshot::get_from(&mut conn, SNAPSHOT_KEY.to_string()) {
+		Ok(snapshot) => app.restore_counter(snapshot, clock.now()),
+		Err(err @ ConfigError::Database(_)) => {
+			panic!("Failed to restore counter: {err}")
+		}
+		// a snapshot that cannot be read back is as good as none
+		Err(_) => (),
+	}
+	refresh_stats(&mut conn, &mut app);
+	app.load_tasks(&mut conn);
+	let mut saved_counter = app.counter.clone();
 	let mut interval = interval(Duration::from_secs(1));
+	// elapsed time is measured against the clock, so there is no need to
+	// catch up on missed ticks
+	interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
 	interval.tick().await; // first tick is immediate
 	let mut event_stream = crossterm::event::EventStream::new().fuse();
 
@@ -54,11 +82,21 @@ async fn main() -> Result<(), io::Error> {
 			tokio::select! {
 				event = event_stream.select_next_some() => {
 					if let Ok(Event::Key(key_event)) = event {
-						let should_stop = app.handle_key_event(key_event);
+						// account for the time before the key was pressed
+						app.tick(&clock);
+						let should_stop =
+							app.handle_key_event(key_event, &mut conn);
 						stop_lock.store(should_stop, Ordering::SeqCst);
+						save_history(&mut conn, &mut app);
 						if should_stop {
 							break;
 						}
+						save_counter(
+							&mut conn,
+							&clock,
+							&app.counter,
+							&mut saved_counter,
+						);
 						app.draw_with(&mut terminal);
 					}
 				}
@@ -67,13 +105,54 @@ async fn main() -> Result<(), io::Error> {
 				}
 			}
 		}
-		app.counter.work(|msg| {
-			if opts.notify {
-				Notification::from(msg).show().unwrap();
-			}
-		});
+		app.tick(&clock);
+		save_counter(&mut conn, &clock, &app.counter, &mut saved_counter);
+		save_history(&mut conn, &mut app);
+		if app.stats.today != Local::now().date_naive() {
+			refresh_stats(&mut conn, &mut app);
+		}
 		app.draw_with(&mut terminal);
 	}
 
 	Ok(())
 }
+
+/// Persists the sessions and interruptions recorded since they were last
+/// saved.
+fn save_history(conn: &mut SqliteConnection, app: &mut App) {
+	let sessions = app.take_sessions();
+	let ended_any = !sessions.is_empty();
+	for session in sessions {
+		let session =
+			create_session(conn, session).expect("Failed to save session");
+		credit_session(conn, &session).expect("Failed to credit task");
+	}
+	for interruption in app.take_interruptions() {
+		create_interruption(conn, interruption)
+			.expect("Failed to save interruption");
+	}
+	if ended_any {
+		refresh_stats(conn, app);
+		app.load_tasks(conn);
+	}
+}
+
+fn refresh_stats(conn: &mut SqliteConnection, app: &mut App) {
+	app.stats = Stats::load(conn).expect("Failed to load statistics");
+}
+
+/// Persists the counter if it has changed since it was last saved.
+fn save_counter<C: Clock>(
+	conn: &mut SqliteConnection,
+	clock: &C,
+	counter: &Counter,
+	saved: &mut Counter,
+) {
+	if counter != saved {
+		counter
+			.snapshot(clock.now())
+			.set_into(conn, SNAPSHOT_KEY.to_string())
+			.expect("Failed to save counter");
+		*saved = counter.clone();
+	}
+}
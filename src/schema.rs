@@ -2,6 +2,33 @@ table! {
 	config (key) {
 		key -> Text,
 		data -> Binary,
+		version -> Integer,
+	}
+}
+
+table! {
+	interruptions (id) {
+		id -> Integer,
+		session_start -> BigInt,
+		at -> BigInt,
+		kind -> Integer,
+		note -> Nullable<Text>,
+		session_id -> Nullable<Integer>,
+	}
+}
+
+table! {
+	sessions (id) {
+		id -> Integer,
+		started -> BigInt,
+		ended -> BigInt,
+		kind -> Integer,
+		outcome -> Integer,
+		task_id -> Nullable<Integer>,
+		internal_interruptions -> Integer,
+		external_interruptions -> Integer,
+		counted -> Integer,
+		clover -> Bool,
 	}
 }
 
@@ -12,7 +39,12 @@ table! {
 		focus_req -> Integer,
 		focus_done -> Integer,
 		box_cat -> Integer,
+		status -> Integer,
+		position -> Integer,
 	}
 }
 
-allow_tables_to_appear_in_same_query!(config, tasks,);
+joinable!(interruptions -> sessions (session_id));
+joinable!(sessions -> tasks (task_id));
+
+allow_tables_to_appear_in_same_query!(config, interruptions, sessions, tasks,);
@@ -0,0 +1,257 @@
+use clap::ValueEnum;
+
+pub use crate::ui::Theme;
+use crate::{
+	app::{parse_ratio, AppOpts},
+	counter::Counter,
+};
+
+/// A setting that can be changed on the Settings page.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Setting {
+	Mode,
+	FocusTime,
+	FlowMin,
+	ShortBreak,
+	LongBreak,
+	CloverLength,
+	CloverBreakBonus,
+	BreakRatio,
+	BreakPolicy,
+	AutoBreak,
+	AutoFocus,
+	BreakWarnings,
+	FocusWarnings,
+	KanbanColumns,
+	Notify,
+	Ascii,
+	Theme,
+}
+
+impl Setting {
+	pub const ALL: [Setting; 17] = [
+		Setting::Mode,
+		Setting::FocusTime,
+		Setting::FlowMin,
+		Setting::ShortBreak,
+		Setting::LongBreak,
+		Setting::CloverLength,
+		Setting::CloverBreakBonus,
+		Setting::BreakRatio,
+		Setting::BreakPolicy,
+		Setting::AutoBreak,
+		Setting::AutoFocus,
+		Setting::BreakWarnings,
+		Setting::FocusWarnings,
+		Setting::KanbanColumns,
+		Setting::Notify,
+		Setting::Ascii,
+		Setting::Theme,
+	];
+
+	pub fn label(&self) -> &'static str {
+		match self {
+			Setting::Mode => "Mode",
+			Setting::FocusTime => "Focus session (s)",
+			Setting::FlowMin => "Minimum flowtime session (s)",
+			Setting::ShortBreak => "Short break (s)",
+			Setting::LongBreak => "Long break (s)",
+			Setting::CloverLength => "Sessions per clover",
+			Setting::CloverBreakBonus => "Clover break bonus (s)",
+			Setting::BreakRatio => "Focus per second of break",
+			Setting::BreakPolicy => "When break runs out",
+			Setting::AutoBreak => "Start breaks automatically",
+			Setting::AutoFocus => "Start focus automatically",
+			Setting::BreakWarnings => "Break warnings (s)",
+			Setting::FocusWarnings => "Focus warnings (s)",
+			Setting::KanbanColumns => "Kanban columns",
+			Setting::Notify => "Notifications",
+			Setting::Ascii => "ASCII art",
+			Setting::Theme => "Theme",
+		}
+	}
+
+	/// Key under which the setting is saved, which it is also named after on
+	/// the command line and in the environment.
+	pub fn key(&self) -> &'static str {
+		match self {
+			Setting::Mode => "mode",
+			Setting::FocusTime => "focus_time",
+			Setting::FlowMin => "flow_min",
+			Setting::ShortBreak => "short_break",
+			Setting::LongBreak => "long_break",
+			Setting::CloverLength => "clover_length",
+			Setting::CloverBreakBonus => "clover_break_bonus",
+			Setting::BreakRatio => "break_ratio",
+			Setting::BreakPolicy => "break_policy",
+			Setting::AutoBreak => "auto_break",
+			Setting::AutoFocus => "auto_focus",
+			Setting::BreakWarnings => "break_warnings",
+			Setting::FocusWarnings => "focus_warnings",
+			Setting::KanbanColumns => "kanban_columns",
+			Setting::Notify => "notify",
+			Setting::Ascii => "ascii",
+			Setting::Theme => "theme",
+		}
+	}
+
+	/// Whether the setting is chosen from a set of values, rather than typed.
+	pub fn is_choice(&self) -> bool {
+		matches!(
+			self,
+			Setting::Mode
+				| Setting::BreakPolicy
+				| Setting::AutoBreak
+				| Setting::AutoFocus
+				| Setting::Notify
+				| Setting::Ascii
+				| Setting::Theme
+		)
+	}
+
+	/// The value of the setting, as shown and edited, where `counter` has
+	/// been configured with `opts`.
+	pub fn value(&self, opts: &AppOpts, counter: &Counter) -> String {
+		match self {
+			Setting::Mode => counter.mode().to_string(),
+			Setting::FocusTime => counter.original_focus_time().to_string(),
+			Setting::FlowMin => counter.flow_min().to_string(),
+			Setting::ShortBreak => counter.short_break().to_string(),
+			Setting::LongBreak => counter.long_break().to_string(),
+			Setting::CloverLength => counter.clover_length().to_string(),
+			Setting::CloverBreakBonus => {
+				counter.clover_break_bonus().to_string()
+			}
+			Setting::BreakRatio => counter.break_ratio().to_string(),
+			// the counter reports the policy in effect, not the one chosen
+			Setting::BreakPolicy => {
+				opts.break_policy.unwrap_or_default().to_string()
+			}
+			Setting::AutoBreak => on_off(counter.auto_break()),
+			Setting::AutoFocus => on_off(counter.auto_focus()),
+			Setting::BreakWarnings => join(counter.break_warnings()),
+			Setting::FocusWarnings => join(counter.focus_warnings()),
+			Setting::KanbanColumns => opts.kanban_columns().join(","),
+			Setting::Notify => on_off(opts.notify.unwrap_or_default()),
+			Setting::Ascii => on_off(opts.ascii.unwrap_or_default()),
+			Setting::Theme => opts.theme.unwrap_or_default().to_string(),
+		}
+	}
+
+	/// Chooses the value `by` values along, if the setting is chosen from a
+	/// set of values.
+	pub fn cycle(&self, opts: &mut AppOpts, by: i32) {
+		match self {
+			Setting::Mode => {
+				opts.mode = Some(cycle(opts.mode.unwrap_or_default(), by))
+			}
+			Setting::BreakPolicy => {
+				opts.break_policy =
+					Some(cycle(opts.break_policy.unwrap_or_default(), by))
+			}
+			Setting::AutoBreak => toggle(&mut opts.auto_break),
+			Setting::AutoFocus => toggle(&mut opts.auto_focus),
+			Setting::Notify => toggle(&mut opts.notify),
+			Setting::Ascii => toggle(&mut opts.ascii),
+			Setting::Theme => {
+				opts.theme = Some(cycle(opts.theme.unwrap_or_default(), by))
+			}
+			_ => (),
+		}
+	}
+
+	/// Sets the setting from `input`, or explains why it cannot be.
+	pub fn set(&self, opts: &mut AppOpts, input: &str) -> Result<(), String> {
+		let input = input.trim();
+		match self {
+			Setting::FocusTime => {
+				opts.focus_time = Some(parse_secs(input, 1)?);
+			}
+			Setting::FlowMin => opts.flow_min = Some(parse_secs(input, 0)?),
+			Setting::ShortBreak => {
+				opts.short_break = Some(parse_secs(input, 0)?)
+			}
+			Setting::LongBreak => opts.long_break = Some(parse_secs(input, 0)?),
+			Setting::CloverLength => {
+				opts.clover_length =
+					Some(
+						input.parse().ok().filter(|&length| length > 0).ok_or(
+							"enter a number of sessions from 1 to 255",
+						)?,
+					)
+			}
+			Setting::CloverBreakBonus => {
+				opts.clover_break_bonus = Some(parse_secs(input, 0)?)
+			}
+			Setting::BreakRatio => opts.break_ratio = Some(parse_ratio(input)?),
+			Setting::BreakWarnings => {
+				opts.break_warnings = Some(parse_warnings(input)?)
+			}
+			Setting::FocusWarnings => {
+				opts.focus_warnings = Some(parse_warnings(input)?)
+			}
+			Setting::KanbanColumns => {
+				let columns: Vec<String> = input
+					.split(',')
+					.map(|name| name.trim().to_string())
+					.collect();
+				if columns.iter().any(String::is_empty) {
+					return Err("enter column names separated by commas".into());
+				}
+				opts.kanban_columns = Some(columns);
+			}
+			_ => self.cycle(opts, 1),
+		}
+		Ok(())
+	}
+}
+
+/// A setting being typed in on the Settings page.
+#[derive(Clone, Debug)]
+pub struct SettingEdit {
+	pub setting: Setting,
+	pub input: String,
+	/// Why the input could not be set, if it could not
+	pub error: Option<String>,
+}
+
+fn on_off(value: bool) -> String {
+	match value {
+		true => "on".to_string(),
+		false => "off".to_string(),
+	}
+}
+
+fn join(secs: &[u32]) -> String {
+	secs.iter().map(u32::to_string).collect::<Vec<_>>().join(",")
+}
+
+fn toggle(value: &mut Option<bool>) {
+	*value = Some(!value.unwrap_or_default());
+}
+
+/// The variant of `value` that is `by` variants along, wrapping around.
+fn cycle<T: ValueEnum + PartialEq>(value: T, by: i32) -> T {
+	let variants = T::value_variants();
+	let idx = variants.iter().position(|x| *x == value).unwrap_or(0);
+	let idx = (idx as i32 + by).rem_euclid(variants.len() as i32);
+	variants[idx as usize].clone()
+}
+
+fn parse_secs(input: &str, min: u32) -> Result<u32, String> {
+	input
+		.parse()
+		.ok()
+		.filter(|&secs| secs >= min)
+		.ok_or_else(|| format!("enter a number of seconds, at least {min}"))
+}
+
+fn parse_warnings(input: &str) -> Result<Vec<u32>, String> {
+	input
+		.split(',')
+		.map(str::trim)
+		.filter(|secs| !secs.is_empty())
+		.map(|secs| parse_secs(secs, 0))
+		.collect::<Result<_, _>>()
+		.map_err(|_| "enter numbers of seconds separated by commas".into())
+}
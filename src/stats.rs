@@ -0,0 +1,136 @@
+use std::time::SystemTime;
+
+use chrono::{DateTime, Days, Local, NaiveDate};
+use diesel::{QueryResult, SqliteConnection};
+
+use crate::{
+	counter::{Phase, PhaseOutcome},
+	db::{get_sessions, Session},
+};
+
+/// Number of days of history that statistics are collected over.
+pub const HISTORY_DAYS: usize = 28;
+
+/// Statistics on the focus sessions and breaks of the last
+/// [`HISTORY_DAYS`] days.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+	/// Day that the statistics are up to, inclusive
+	pub today: NaiveDate,
+	/// Seconds of focus on each day, oldest first and ending today
+	pub daily_focus: Vec<u64>,
+	/// Poms completed on each day, oldest first and ending today
+	pub daily_poms: Vec<u32>,
+	/// Clovers completed over the whole history
+	pub clovers: u32,
+	/// Number of breaks taken over the whole history
+	pub breaks: u32,
+	/// Seconds of break taken over the whole history
+	pub break_time: u64,
+	/// Number of days in a row within the history, up to today or yesterday,
+	/// on which at least one pom was completed
+	pub streak: u32,
+}
+
+impl Stats {
+	/// Collects statistics from the `sessions` of the [`HISTORY_DAYS`] days
+	/// up to `today`, where `day_of` gives the day that a time in seconds
+	/// since the UNIX epoch falls on.
+	pub fn new<F: Fn(i64) -> NaiveDate>(
+		sessions: &[Session],
+		today: NaiveDate,
+		day_of: F,
+	) -> Self {
+		let mut stats = Stats {
+			today,
+			daily_focus: vec![0; HISTORY_DAYS],
+			daily_poms: vec![0; HISTORY_DAYS],
+			..Default::default()
+		};
+
+		for session in sessions {
+			let days_ago = (today - day_of(session.started)).num_days();
+			let Some(day) = usize::try_from(days_ago)
+				.ok()
+				.and_then(|days_ago| HISTORY_DAYS.checked_sub(days_ago + 1))
+			else {
+				continue;
+			};
+			let counted = session.counted.max(0) as u64;
+			match session.phase() {
+				Phase::Focus => {
+					stats.daily_focus[day] += counted;
+					if session.outcome() == PhaseOutcome::Completed {
+						stats.daily_poms[day] += 1;
+					}
+					if session.clover {
+						stats.clovers += 1;
+					}
+				}
+				Phase::Break => {
+					stats.breaks += 1;
+					stats.break_time += counted;
+				}
+			}
+		}
+
+		// today does not break the streak until it is over
+		let mut days = stats.daily_poms.iter().rev().peekable();
+		days.next_if_eq(&&0);
+		stats.streak = days.take_while(|&&poms| poms > 0).count() as u32;
+		stats
+	}
+
+	/// Collects statistics from the sessions recorded in the database, by
+	/// days in local time.
+	pub fn load(conn: &mut SqliteConnection) -> QueryResult<Self> {
+		let today = Local::now().date_naive();
+		let from = today - Days::new(HISTORY_DAYS as u64 - 1);
+		let to = today + Days::new(1);
+		let sessions =
+			get_sessions(conn, start_of_day(from), start_of_day(to))?;
+
+		Ok(Stats::new(&sessions, today, |secs| {
+			DateTime::from_timestamp(secs, 0)
+				.map_or(today, |time| time.with_timezone(&Local).date_naive())
+		}))
+	}
+
+	pub fn poms_today(&self) -> u32 {
+		self.daily_poms.last().copied().unwrap_or(0)
+	}
+
+	pub fn focus_today(&self) -> u64 {
+		self.daily_focus.last().copied().unwrap_or(0)
+	}
+
+	/// Poms completed over the whole history.
+	pub fn poms(&self) -> u32 {
+		self.daily_poms.iter().sum()
+	}
+
+	/// Seconds of focus over the whole history.
+	pub fn focus_time(&self) -> u64 {
+		self.daily_focus.iter().sum()
+	}
+
+	/// Average length of a break in seconds.
+	pub fn average_break(&self) -> u64 {
+		self.break_time.checked_div(self.breaks.into()).unwrap_or(0)
+	}
+
+	/// Seconds of break taken per second of focus.
+	pub fn break_usage(&self) -> f64 {
+		match self.focus_time() {
+			0 => 0.0,
+			focus_time => self.break_time as f64 / focus_time as f64,
+		}
+	}
+}
+
+/// The time at which `day` starts in local time.
+fn start_of_day(day: NaiveDate) -> SystemTime {
+	day.and_hms_opt(0, 0, 0)
+		.and_then(|time| time.and_local_timezone(Local).earliest())
+		.map_or(SystemTime::UNIX_EPOCH, SystemTime::from)
+}
@@ -6,23 +6,28 @@ mod styles;
 use ratatui::{
 	layout::{Alignment, Constraint, Layout, Rect},
 	text::{Line, Span},
+	widgets::{Cell, Clear, Paragraph, Row, Table, TableState},
 	Frame,
 };
+pub use styles::Theme;
 use styles::*;
 use tui_flusso_widgets::AlignedTabs;
 
-use crate::app::App;
+use crate::{
+	app::{App, Prompt},
+	forms::TaskPicker,
+};
 
 // AppPage::Help must ALWAYS be the first meta page
 #[derive(Clone, Copy)]
 pub enum AppPage {
 	Main(pages::Main),
-	// TODO: create pages for these
-	EisenhowerMat,
-	Kanban,
-	Tasks,
+	EisenhowerMat(pages::EisenhowerMat),
+	Kanban(pages::Kanban),
+	Tasks(pages::Tasks),
+	Stats(pages::Stats),
 	Help(pages::Help),
-	Settings,
+	Settings(pages::Settings),
 }
 
 impl Default for AppPage {
@@ -41,8 +46,8 @@ impl AppPage {
 
 	pub fn toggle_settings(&self) -> Self {
 		match self {
-			AppPage::Help(_) => AppPage::default(),
-			_ => AppPage::Settings,
+			AppPage::Settings(_) => AppPage::default(),
+			_ => AppPage::Settings(pages::Settings::default()),
 		}
 	}
 
@@ -54,27 +59,42 @@ impl AppPage {
 
 	pub fn next_non_meta(&self) -> Self {
 		match self {
-			AppPage::Main(_) => AppPage::EisenhowerMat,
-			AppPage::EisenhowerMat => AppPage::Kanban,
-			AppPage::Kanban => AppPage::Tasks,
-			AppPage::Tasks => AppPage::Main(pages::Main::default()),
+			AppPage::Main(_) => {
+				AppPage::EisenhowerMat(pages::EisenhowerMat::default())
+			}
+			AppPage::EisenhowerMat(_) => {
+				AppPage::Kanban(pages::Kanban::default())
+			}
+			AppPage::Kanban(_) => AppPage::Tasks(pages::Tasks::default()),
+			AppPage::Tasks(_) => AppPage::Stats(pages::Stats::default()),
+			AppPage::Stats(_) => AppPage::Main(pages::Main::default()),
 			x => *x,
 		}
 	}
 
 	pub fn prev_non_meta(&self) -> Self {
 		match self {
-			AppPage::Main(_) => AppPage::Tasks,
-			AppPage::Tasks => AppPage::Kanban,
-			AppPage::Kanban => AppPage::EisenhowerMat,
-			AppPage::EisenhowerMat => AppPage::Main(pages::Main::default()),
+			AppPage::Main(_) => AppPage::Stats(pages::Stats::default()),
+			AppPage::Stats(_) => AppPage::Tasks(pages::Tasks::default()),
+			AppPage::Tasks(_) => AppPage::Kanban(pages::Kanban::default()),
+			AppPage::Kanban(_) => {
+				AppPage::EisenhowerMat(pages::EisenhowerMat::default())
+			}
+			AppPage::EisenhowerMat(_) => AppPage::Main(pages::Main::default()),
 			x => *x,
 		}
 	}
 
-	pub fn scroll_by(&mut self, scroll: i16) {
-		if let AppPage::Help(help) = self {
-			help.scroll_by(scroll)
+	/// Scrolls the page by `scroll` lines, or moves its cursor by as many
+	/// rows of the `rows` it lists.
+	pub fn scroll_by(&mut self, scroll: i16, rows: usize) {
+		match self {
+			AppPage::Help(help) => help.scroll_by(scroll),
+			AppPage::EisenhowerMat(matrix) => matrix.select_by(scroll, rows),
+			AppPage::Kanban(board) => board.select_by(scroll, rows),
+			AppPage::Tasks(tasks) => tasks.select_by(scroll, rows),
+			AppPage::Settings(settings) => settings.select_by(scroll, rows),
+			_ => (),
 		}
 	}
 }
@@ -85,19 +105,20 @@ pub trait Page {
 
 impl Page for AppPage {
 	fn render(&self, area: Rect, f: &mut Frame, app: &App) {
+		let styles = app.opts.theme.unwrap_or_default().styles();
 		let chunks = Layout::default()
 			.constraints(vec![Constraint::Length(3), Constraint::Min(0)])
 			.split(area);
 		let titles: Vec<Line> =
-			["Counter", "Eisenhower's Matrix", "Kanban", "Tasks"]
+			["Counter", "Eisenhower's Matrix", "Kanban", "Tasks", "Stats"]
 				.iter()
-				.map(|t| Line::from(Span::styled(*t, *ELEM)))
+				.map(|t| Line::from(Span::styled(*t, styles.elem)))
 				.collect();
 
 		f.render_widget(
 			AlignedTabs::new(titles)
-				.block(block_std().title("Flussomodoro"))
-				.highlight_style(*ELEM_SEL)
+				.block(block_std(styles).title("Flussomodoro"))
+				.highlight_style(styles.elem_sel)
 				.alignment(Alignment::Center)
 				.select(AppPage::idx_of(self)),
 			chunks[0],
@@ -105,9 +126,91 @@ impl Page for AppPage {
 
 		match self {
 			AppPage::Main(x) => x.render(chunks[1], f, app),
+			AppPage::EisenhowerMat(x) => x.render(chunks[1], f, app),
+			AppPage::Kanban(x) => x.render(chunks[1], f, app),
+			AppPage::Tasks(x) => x.render(chunks[1], f, app),
+			AppPage::Stats(x) => x.render(chunks[1], f, app),
 			AppPage::Help(x) => x.render(chunks[1], f, app),
-			// TODO: render new pages
-			_ => (),
+			AppPage::Settings(x) => x.render(chunks[1], f, app),
+		}
+		if let Some(picker) = &app.task_picker {
+			render_task_picker(picker, styles, chunks[1], f);
+		}
+		if let Some(prompt) = &app.prompt {
+			render_prompt(prompt, styles, area, f);
 		}
 	}
 }
+
+/// Renders `picker` over the middle of `area`.
+fn render_task_picker(
+	picker: &TaskPicker,
+	styles: &Styles,
+	area: Rect,
+	f: &mut Frame,
+) {
+	let width = area.width.min(50);
+	let height = area.height.min(12);
+	let area = Rect {
+		x: area.x + (area.width - width) / 2,
+		y: area.y + (area.height - height) / 2,
+		width,
+		height,
+	};
+	let block = block_std(styles)
+		.title("Pick a task ([Enter] work on it, [Esc] cancel)")
+		.border_style(styles.elem_sel);
+	let chunks = Layout::default()
+		.constraints(vec![Constraint::Length(1), Constraint::Min(0)])
+		.split(block.inner(area));
+
+	let matches = picker.matches();
+	let rows = matches.iter().map(|task| {
+		Row::new(vec![
+			Cell::from(task.name.as_str()),
+			Cell::from(task.quadrant_name()),
+			Cell::from(format!("{}/{}", task.focus_done, task.focus_req)),
+		])
+	});
+	let table = Table::new(rows, [
+		Constraint::Length(18),
+		Constraint::Length(10),
+		Constraint::Min(5),
+	])
+	.style(styles.std)
+	.row_highlight_style(styles.elem_sel)
+	.highlight_symbol("> ");
+	let mut state = TableState::default()
+		.with_selected((!matches.is_empty()).then_some(picker.selected));
+
+	f.render_widget(Clear, area);
+	f.render_widget(block, area);
+	f.render_widget(
+		Paragraph::new(format!("/{}", picker.query)).style(styles.elem_sel),
+		chunks[0],
+	);
+	f.render_stateful_widget(table, chunks[1], &mut state);
+	f.set_cursor_position((
+		chunks[0].x + 1 + picker.query.chars().count() as u16,
+		chunks[0].y,
+	));
+}
+
+/// Renders `prompt` over the bottom of `area`.
+fn render_prompt(prompt: &Prompt, styles: &Styles, area: Rect, f: &mut Frame) {
+	let area = Rect {
+		y: area.bottom().saturating_sub(3),
+		height: area.height.min(3),
+		..area
+	};
+	let input = Paragraph::new(prompt.input.as_str())
+		.block(block_std(styles).title(prompt.title.as_str()))
+		.style(styles.elem_sel);
+
+	f.render_widget(Clear, area);
+	f.render_widget(input, area);
+	f.set_cursor_position((
+		area.x + 1 + prompt.input.chars().count() as u16,
+		area.y + 1,
+	));
+}
@@ -1,30 +1,49 @@
-#![allow(clippy::declare_interior_mutable_const)]
-
 use std::{cmp::min, sync::OnceLock};
 
+use chrono::{Datelike, Days};
 use ratatui::{
 	layout::{Constraint, Direction, Layout, Rect},
 	style::Modifier,
 	text::{Line, Span},
-	widgets::{Gauge, Paragraph, Widget},
+	widgets::{
+		Bar,
+		BarChart,
+		BarGroup,
+		Cell,
+		Gauge,
+		List,
+		ListState,
+		Paragraph,
+		Row,
+		Sparkline,
+		Table,
+		TableState,
+		Widget,
+		Wrap,
+	},
 	Frame,
 };
 use time_fmt::FormattedTime;
 use tui_flusso_widgets::{Ascii, CircularGauge};
 
 use super::{styles::*, Page};
-use crate::app::App;
+use crate::{
+	app::App,
+	counter::{Counter, CounterMode},
+	db::{QUADRANTS, QUADRANT_NAMES},
+	forms::{TaskField, TaskForm},
+	settings::{Setting, SettingEdit},
+	stats::{Stats as History, HISTORY_DAYS},
+};
 
 #[derive(Clone, Copy, Default)]
 pub struct Main {}
 
 impl Page for Main {
 	fn render(&self, area: Rect, f: &mut Frame, app: &App) {
-		let focus_time = app.counter.focus_time();
-		let original_focus_time = app.counter.original_focus_time();
-
+		let styles = app.opts.theme.unwrap_or_default().styles();
 		let chunks = Layout::default()
-			.constraints(vec![Constraint::Length(5), Constraint::Min(10)])
+			.constraints(vec![Constraint::Length(6), Constraint::Min(10)])
 			.split(area);
 		let status_session_chunks = Layout::default()
 			.direction(Direction::Horizontal)
@@ -43,68 +62,559 @@ impl Page for Main {
 
 		f.render_widget(
 			Paragraph::new(vec![
-				Line::from(Span::raw("Current task: TBD!")),
-				Line::from(Span::raw("Task progress: TBD!")),
+				Line::from(match app.current_task() {
+					Some(task) => format!("Current task: {}", task.name),
+					None => "Current task: none ([c] on a task)".to_string(),
+				}),
+				Line::from(match app.current_task() {
+					Some(task) if task.is_complete() => format!(
+						"Task progress: {}/{} (done)",
+						task.focus_done, task.focus_req
+					),
+					Some(task) => format!(
+						"Task progress: {}/{}",
+						task.focus_done, task.focus_req
+					),
+					None => "Task progress: -".to_string(),
+				}),
+				Line::from(format!("Status: {}", app.counter.work_state())),
 				Line::from(format!(
-					"Status: {}",
-					app.counter.work_state().to_string()
+					"Interruptions: {} internal, {} external",
+					app.counter.interruptions().internal,
+					app.counter.interruptions().external
 				)),
 			])
-			.block(block_std().title("Status"))
-			.style(*STD),
+			.block(block_std(styles).title("Status"))
+			.style(styles.std),
 			status_session_chunks[0],
 		);
 		f.render_widget(
 			Gauge::default()
-				.block(block_std().title("Session"))
-				.gauge_style(*X_GAUGE)
-				.label(format!("{}/4", app.counter.pom()))
+				.block(block_std(styles).title("Session"))
+				.gauge_style(styles.x_gauge)
+				.label(format!(
+					"{}/{}",
+					app.counter.pom(),
+					app.counter.clover_length()
+				))
 				.use_unicode(true)
-				.ratio(app.counter.pom() as f64 / 4.0),
+				.ratio(
+					app.counter.pom() as f64
+						/ app.counter.clover_length() as f64,
+				),
 			status_session_chunks[1],
 		);
-		if app.opts.ascii {
-			f.render_widget(focus_ascii(focus_time), focus_break_chunks[0]);
+		if app.opts.ascii.unwrap_or_default() {
+			f.render_widget(
+				focus_ascii(&app.counter, styles),
+				focus_break_chunks[0],
+			);
 		} else {
 			f.render_widget(
-				focus_gauge(focus_time, original_focus_time),
+				focus_gauge(&app.counter, styles),
 				focus_break_chunks[0],
 			);
 		}
 		f.render_widget(
-			break_ascii(app.counter.break_time()),
+			break_ascii(&app.counter, styles),
 			focus_break_chunks[1],
 		);
 	}
 }
 
-fn focus_gauge(remaining: u16, initial: u16) -> impl Widget {
-	let ratio = (initial - remaining) as f64 / initial as f64;
+fn focus_gauge(counter: &Counter, styles: &Styles) -> impl Widget {
+	let focus_time = FormattedTime::from(counter.focus_time());
+	let (ratio, label) = match counter.mode() {
+		// there is no end to measure progress against, so show progress
+		// towards the minimum length instead
+		CounterMode::Flowtime if counter.focused() >= counter.flow_min() => {
+			(1.0, format!("{focus_time} (flowing)"))
+		}
+		CounterMode::Flowtime => {
+			let ratio = counter.focused() as f64 / counter.flow_min() as f64;
+			(ratio, format!("{focus_time} ({:.1}% of minimum)", ratio * 100.0))
+		}
+		_ => {
+			let ratio =
+				counter.focused() as f64 / counter.original_focus_time() as f64;
+			(ratio, format!("{focus_time} ({:.1}%)", ratio * 100.0))
+		}
+	};
 
 	CircularGauge::default()
-		.block(block_std().title("Focus"))
-		.gauge_style(*FOCUS)
-		.label(Span::styled(
-			format!(
-				"{} ({:.1}%)",
-				FormattedTime::from(remaining),
-				ratio * 100.0
-			),
-			FOCUS.add_modifier(Modifier::ITALIC),
-		))
+		.block(block_std(styles).title(focus_title(counter)))
+		.gauge_style(styles.focus)
+		.label(Span::styled(label, styles.focus.add_modifier(Modifier::ITALIC)))
 		.ratio(ratio)
 }
 
-pub fn focus_ascii(focus_time: u16) -> impl Widget {
-	Ascii::new(FormattedTime::from(focus_time))
-		.block(block_std().title("Focus"))
-		.style(*FOCUS)
+pub fn focus_ascii(counter: &Counter, styles: &Styles) -> impl Widget {
+	Ascii::new(FormattedTime::from(counter.focus_time()))
+		.block(block_std(styles).title(focus_title(counter)))
+		.style(styles.focus)
 }
 
-fn break_ascii(break_time: u16) -> impl Widget {
-	Ascii::new(FormattedTime::from(break_time))
-		.block(block_std().title("Break"))
-		.style(*BREAK)
+fn focus_title(counter: &Counter) -> &'static str {
+	match counter.mode() {
+		CounterMode::Flowtime => "Focus (flowtime)",
+		_ => "Focus",
+	}
+}
+
+fn break_ascii(counter: &Counter, styles: &Styles) -> impl Widget {
+	let text = match counter.overdraft() {
+		0 => FormattedTime::from(counter.break_time()).to_string(),
+		overdraft => format!("-{}", FormattedTime::from(overdraft)),
+	};
+
+	Ascii::new(text)
+		.block(
+			block_std(styles).title(format!(
+				"Break (on expiry: {})",
+				counter.break_policy()
+			)),
+		)
+		.style(styles.brk)
+}
+
+/// Moves the `selected` row by `by` rows, staying within the `rows` listed.
+fn select_by(selected: &mut usize, by: i16, rows: usize) {
+	let row = selected.saturating_add_signed(by as isize);
+	*selected = row.min(rows.saturating_sub(1));
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct EisenhowerMat {
+	/// Quadrant in focus, by `box_cat`
+	quadrant: i32,
+	/// Row selected within the quadrant in focus
+	selected: usize,
+}
+
+impl EisenhowerMat {
+	pub fn quadrant(&self) -> i32 {
+		self.quadrant
+	}
+
+	pub fn selected(&self) -> usize {
+		self.selected
+	}
+
+	/// Focuses `row` of quadrant `box_cat`.
+	pub fn focus(&mut self, box_cat: i32, row: usize) {
+		self.quadrant = box_cat.rem_euclid(QUADRANTS);
+		self.selected = row;
+	}
+
+	/// Focuses the quadrant `by` quadrants along, wrapping around.
+	pub fn focus_by(&mut self, by: i32) {
+		self.focus(self.quadrant + by, 0);
+	}
+
+	/// Moves the cursor by `by` rows, staying within the `rows` listed.
+	pub fn select_by(&mut self, by: i16, rows: usize) {
+		select_by(&mut self.selected, by, rows);
+	}
+}
+
+impl Page for EisenhowerMat {
+	fn render(&self, area: Rect, f: &mut Frame, app: &App) {
+		let styles = app.opts.theme.unwrap_or_default().styles();
+		let rows = Layout::default()
+			.constraints(vec![Constraint::Ratio(1, 2); 2])
+			.split(area);
+		let cells = rows.iter().flat_map(|&row| {
+			Layout::default()
+				.direction(Direction::Horizontal)
+				.constraints(vec![Constraint::Ratio(1, 2); 2])
+				.split(row)
+				.to_vec()
+		});
+
+		for (box_cat, cell) in (0..QUADRANTS).zip(cells) {
+			let items: Vec<String> = app
+				.quadrant_tasks(box_cat)
+				.map(|task| {
+					format!(
+						"{} {}/{}",
+						task.name, task.focus_done, task.focus_req
+					)
+				})
+				.collect();
+			let focused = box_cat == self.quadrant;
+			let title = format!(
+				"[{}] {}",
+				box_cat + 1,
+				QUADRANT_NAMES[box_cat as usize]
+			);
+			let block = match focused {
+				true => block_std(styles).border_style(styles.elem_sel),
+				false => block_std(styles),
+			};
+			let mut state = ListState::default().with_selected(
+				(focused && !items.is_empty()).then_some(self.selected),
+			);
+			f.render_stateful_widget(
+				List::new(items)
+					.block(block.title(title))
+					.style(styles.std)
+					.highlight_style(styles.elem_sel)
+					.highlight_symbol("> "),
+				cell,
+				&mut state,
+			);
+		}
+	}
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct Kanban {
+	/// Column in focus, by `status`
+	column: i32,
+	/// Row selected within the column in focus
+	selected: usize,
+}
+
+impl Kanban {
+	pub fn column(&self) -> i32 {
+		self.column
+	}
+
+	pub fn selected(&self) -> usize {
+		self.selected
+	}
+
+	/// Focuses `row` of column `status`.
+	pub fn focus(&mut self, status: i32, row: usize) {
+		self.column = status;
+		self.selected = row;
+	}
+
+	/// Focuses the column `by` columns along, out of `columns`, wrapping
+	/// around.
+	pub fn focus_by(&mut self, by: i32, columns: i32) {
+		self.focus((self.column + by).rem_euclid(columns.max(1)), 0);
+	}
+
+	/// Moves the cursor by `by` rows, staying within the `rows` listed.
+	pub fn select_by(&mut self, by: i16, rows: usize) {
+		select_by(&mut self.selected, by, rows);
+	}
+}
+
+impl Page for Kanban {
+	fn render(&self, area: Rect, f: &mut Frame, app: &App) {
+		let styles = app.opts.theme.unwrap_or_default().styles();
+		let columns = app.opts.kanban_columns();
+		let cells = Layout::default()
+			.direction(Direction::Horizontal)
+			.constraints(vec![
+				Constraint::Ratio(1, columns.len() as u32);
+				columns.len()
+			])
+			.split(area);
+
+		for ((status, name), &cell) in (0..).zip(columns).zip(cells.iter()) {
+			let items: Vec<String> = app
+				.column_tasks(status)
+				.iter()
+				.map(|task| {
+					format!(
+						"{} {}/{}",
+						task.name, task.focus_done, task.focus_req
+					)
+				})
+				.collect();
+			let focused = status == self.column;
+			let title = format!("{name} ({})", items.len());
+			let block = match focused {
+				true => block_std(styles).border_style(styles.elem_sel),
+				false => block_std(styles),
+			};
+			let mut state = ListState::default().with_selected(
+				(focused && !items.is_empty()).then_some(self.selected),
+			);
+			f.render_stateful_widget(
+				List::new(items)
+					.block(block.title(title))
+					.style(styles.std)
+					.highlight_style(styles.elem_sel)
+					.highlight_symbol("> "),
+				cell,
+				&mut state,
+			);
+		}
+	}
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct Tasks {
+	selected: usize,
+}
+
+impl Tasks {
+	pub fn selected(&self) -> usize {
+		self.selected
+	}
+
+	pub fn select(&mut self, row: usize) {
+		self.selected = row;
+	}
+
+	/// Moves the cursor by `by` rows, staying within the `rows` listed.
+	pub fn select_by(&mut self, by: i16, rows: usize) {
+		select_by(&mut self.selected, by, rows);
+	}
+}
+
+impl Page for Tasks {
+	fn render(&self, area: Rect, f: &mut Frame, app: &App) {
+		let styles = app.opts.theme.unwrap_or_default().styles();
+		let form_height = match app.task_form {
+			Some(_) => 8,
+			None => 0,
+		};
+		let chunks = Layout::default()
+			.constraints(vec![
+				Constraint::Min(3),
+				Constraint::Length(form_height),
+			])
+			.split(area);
+
+		let rows = app.tasks.iter().map(|task| {
+			let done = if task.is_complete() { " (done)" } else { "" };
+			Row::new(vec![
+				Cell::from(task.name.as_str()),
+				Cell::from(format!(
+					"{}/{}{done}",
+					task.focus_done, task.focus_req
+				)),
+				Cell::from(task.quadrant_name()),
+			])
+		});
+		let table = Table::new(rows, [
+			Constraint::Length(18),
+			Constraint::Length(14),
+			Constraint::Min(10),
+		])
+		.header(Row::new(vec!["Name", "Progress", "Quadrant"]).style(*HEADING))
+		.block(
+			block_std(styles)
+				.title("Tasks ([n] new, [Enter] edit, [d] delete)"),
+		)
+		.style(styles.std)
+		.row_highlight_style(styles.elem_sel)
+		.highlight_symbol("> ");
+		let mut state = TableState::default()
+			.with_selected((!app.tasks.is_empty()).then_some(self.selected));
+		f.render_stateful_widget(table, chunks[0], &mut state);
+
+		if let Some(form) = &app.task_form {
+			render_task_form(form, styles, chunks[1], f);
+		}
+	}
+}
+
+fn render_task_form(
+	form: &TaskForm,
+	styles: &Styles,
+	area: Rect,
+	f: &mut Frame,
+) {
+	let mut lines: Vec<Line> = TaskField::ALL
+		.iter()
+		.map(|&field| {
+			let value = match field {
+				TaskField::Quadrant => format!("< {} >", form.value(field)),
+				_ => form.value(field).to_string(),
+			};
+			let style =
+				if field == form.field { styles.elem_sel } else { styles.std };
+			Line::styled(format!("{}: {value}", field.label()), style)
+		})
+		.collect();
+	lines.push(match &form.error {
+		Some(error) => Line::styled(error.as_str(), styles.focus),
+		None => Line::from("[Tab] next field  [Enter] save  [Esc] cancel"),
+	});
+	let title = match form.task_id {
+		Some(_) => "Edit task",
+		None => "New task",
+	};
+
+	f.render_widget(
+		Paragraph::new(lines)
+			.block(block_std(styles).title(title))
+			.style(styles.std)
+			.wrap(Wrap { trim: true }),
+		area,
+	);
+	if form.field != TaskField::Quadrant {
+		let row = TaskField::ALL.iter().position(|&field| field == form.field);
+		let label = form.field.label().chars().count() + 2;
+		let value = form.value(form.field).chars().count();
+		f.set_cursor_position((
+			area.x + 1 + (label + value) as u16,
+			area.y + 1 + row.unwrap_or(0) as u16,
+		));
+	}
+}
+
+/// Number of days shown in the daily focus bar chart.
+const CHART_DAYS: usize = 14;
+
+#[derive(Clone, Copy, Default)]
+pub struct Stats {}
+
+impl Page for Stats {
+	fn render(&self, area: Rect, f: &mut Frame, app: &App) {
+		let styles = app.opts.theme.unwrap_or_default().styles();
+		let chunks = Layout::default()
+			.constraints(vec![
+				Constraint::Length(6),
+				Constraint::Min(8),
+				Constraint::Length(5),
+			])
+			.split(area);
+		let stats = &app.stats;
+		let focus_time = |secs: u64| FormattedTime::from(secs as u32);
+
+		f.render_widget(
+			Paragraph::new(vec![
+				Line::from(format!(
+					"Today: {} poms, {} of focus",
+					stats.poms_today(),
+					focus_time(stats.focus_today())
+				)),
+				Line::from(format!("Current streak: {} days", stats.streak)),
+				Line::from(format!(
+					"Clovers completed (last {} days): {}",
+					HISTORY_DAYS, stats.clovers
+				)),
+				Line::from(format!(
+					"Average break: {} ({:.0}% of time spent focusing)",
+					focus_time(stats.average_break()),
+					stats.break_usage() * 100.0
+				)),
+			])
+			.block(block_std(styles).title("Summary"))
+			.style(styles.std),
+			chunks[0],
+		);
+		f.render_widget(daily_focus_chart(stats, styles), chunks[1]);
+		f.render_widget(
+			Sparkline::default()
+				.block(
+					block_std(styles).title(format!(
+						"Focus per day (last {HISTORY_DAYS} days)"
+					)),
+				)
+				.data(&stats.daily_focus)
+				.style(styles.focus),
+			chunks[2],
+		);
+	}
+}
+
+fn daily_focus_chart(stats: &History, styles: &Styles) -> impl Widget {
+	let days_ago = (0..).map(|days| stats.today - Days::new(days));
+	let mut bars: Vec<Bar> = stats
+		.daily_focus
+		.iter()
+		.rev()
+		.zip(days_ago)
+		.take(CHART_DAYS)
+		.map(|(&secs, day)| {
+			let weekday = day.weekday().to_string();
+			Bar::default().value(secs / 60).label(Line::from(format!(
+				"{} {}",
+				&weekday[..2],
+				day.day()
+			)))
+		})
+		.collect();
+	bars.reverse();
+
+	BarChart::default()
+		.block(
+			block_std(styles).title(format!(
+				"Focus minutes per day (last {CHART_DAYS} days)"
+			)),
+		)
+		.data(BarGroup::default().bars(&bars))
+		.bar_width(5)
+		.bar_gap(1)
+		.bar_style(styles.focus)
+		.value_style(styles.focus.add_modifier(Modifier::REVERSED))
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct Settings {
+	selected: usize,
+}
+
+impl Settings {
+	pub fn selected(&self) -> usize {
+		self.selected
+	}
+
+	/// Moves the cursor by `by` rows, staying within the `rows` listed.
+	pub fn select_by(&mut self, by: i16, rows: usize) {
+		select_by(&mut self.selected, by, rows);
+	}
+}
+
+impl Page for Settings {
+	fn render(&self, area: Rect, f: &mut Frame, app: &App) {
+		let styles = app.opts.theme.unwrap_or_default().styles();
+		let chunks = Layout::default()
+			.constraints(vec![Constraint::Min(3), Constraint::Length(3)])
+			.split(area);
+
+		let rows = Setting::ALL.iter().map(|setting| {
+			let value = match &app.setting_edit {
+				Some(edit) if edit.setting == *setting => edit.input.clone(),
+				_ if setting.is_choice() => {
+					format!("< {} >", setting.value(&app.opts, &app.counter))
+				}
+				_ => setting.value(&app.opts, &app.counter),
+			};
+			Row::new(vec![Cell::from(setting.label()), Cell::from(value)])
+		});
+		let table =
+			Table::new(rows, [Constraint::Length(30), Constraint::Min(10)])
+				.block(block_std(styles).title("Settings"))
+				.style(styles.std)
+				.row_highlight_style(styles.elem_sel)
+				.highlight_symbol("> ");
+		let mut state = TableState::default().with_selected(self.selected);
+		f.render_stateful_widget(table, chunks[0], &mut state);
+
+		let footer = match &app.setting_edit {
+			Some(SettingEdit { error: Some(error), .. }) => {
+				Line::styled(error.as_str(), styles.focus)
+			}
+			Some(_) => Line::from("[Enter] save  [Esc] cancel"),
+			None => Line::from(
+				"[Enter] edit  [Left/Right] change  [o] close - saved at once",
+			),
+		};
+		f.render_widget(
+			Paragraph::new(footer).block(block_std(styles)).style(styles.std),
+			chunks[1],
+		);
+
+		if let Some(edit) = &app.setting_edit {
+			// past the border, highlight symbol and label column
+			let row = state.offset().abs_diff(self.selected) as u16;
+			f.set_cursor_position((
+				chunks[0].x
+					+ 1 + 2 + 30 + 1
+					+ edit.input.chars().count() as u16,
+				chunks[0].y + 1 + row,
+			));
+		}
+	}
 }
 
 #[derive(Clone, Copy, Default)]
@@ -113,10 +623,10 @@ pub struct Help {
 }
 
 impl Help {
-	const CONTENT: OnceLock<Vec<Line<'static>>> = OnceLock::new();
-
 	fn content() -> Vec<Line<'static>> {
-		Self::CONTENT
+		static CONTENT: OnceLock<Vec<Line<'static>>> = OnceLock::new();
+
+		CONTENT
 			.get_or_init(|| {
 				vec![
 					Line::from(Span::styled("Help", *HEADING)),
@@ -124,12 +634,60 @@ impl Help {
 					Line::from(Span::styled("Global", *HEADING)),
 					Line::from(""),
 					Line::from("[h] - This menu"),
+					Line::from("[o] - Settings"),
 					Line::from("[j/k] - Scroll down/up respectively (where applicable)"),
+					Line::from("[/] - Pick a task to work on by typing part of its name"),
 					Line::from(""),
 					Line::from(Span::styled("Counter", *HEADING)),
 					Line::from(""),
 					Line::from("[p] - Toggle pause"),
 					Line::from("[b] - Toggle break (while not paused)"),
+					Line::from("[e] - End the focus session (flowtime mode)"),
+					Line::from("[a] - Abandon the focus session"),
+					Line::from("[s] - Skip the rest of the break"),
+					Line::from("[u] - Undo the last counter action"),
+					Line::from("[i/I] - Log an internal/external interruption of the focus session"),
+					Line::from(""),
+					Line::from(Span::styled("Tasks", *HEADING)),
+					Line::from(""),
+					Line::from("[j/k] - Select the next/previous task"),
+					Line::from("[n] - Add a task"),
+					Line::from("[Enter] - Edit the selected task"),
+					Line::from("[d] - Delete the selected task"),
+					Line::from("[c] - Work on the selected task, or stop working on it (also on the matrix and board)"),
+					Line::from("[Tab/Shift+Tab] - Move between the fields of a task"),
+					Line::from("[Left/Right] - Change the quadrant of a task"),
+					Line::from(""),
+					Line::from(Span::styled("Eisenhower's Matrix", *HEADING)),
+					Line::from(""),
+					Line::from("[Left/Right] - Move to the previous/next quadrant"),
+					Line::from("[j/k] - Select the next/previous task in the quadrant"),
+					Line::from("[1-4] - Move the selected task to that quadrant"),
+					Line::from(""),
+					Line::from(Span::styled("Kanban", *HEADING)),
+					Line::from(""),
+					Line::from("[Left/Right] - Move to the previous/next column"),
+					Line::from("[j/k] - Select the next/previous card in the column"),
+					Line::from("[Shift+Left/Right] - Move the selected card to the previous/next column"),
+					Line::from("[J/K] - Move the selected card down/up its column"),
+					Line::from(""),
+					Line::from(Span::styled("Settings", *HEADING)),
+					Line::from(""),
+					Line::from("[j/k] - Select the next/previous setting"),
+					Line::from("[Enter] - Edit the selected setting, then save it"),
+					Line::from("[Left/Right] - Change a setting chosen from a set of values"),
+					Line::from("[Esc] - Stop editing without saving"),
+					Line::from(""),
+					Line::from(Span::styled("Task picker", *HEADING)),
+					Line::from(""),
+					Line::from("[Up/Down] - Select the next/previous matching task"),
+					Line::from("[Enter] - Work on the selected task"),
+					Line::from("[Esc] - Close without picking a task"),
+					Line::from(""),
+					Line::from(Span::styled("Prompts", *HEADING)),
+					Line::from(""),
+					Line::from("[Enter] - Submit the input"),
+					Line::from("[Esc] - Cancel (an interruption is still logged, without a note)"),
 				]
 			})
 			.to_owned()
@@ -147,11 +705,12 @@ impl Help {
 }
 
 impl Page for Help {
-	fn render(&self, area: Rect, f: &mut Frame, _: &App) {
+	fn render(&self, area: Rect, f: &mut Frame, app: &App) {
+		let styles = app.opts.theme.unwrap_or_default().styles();
 		f.render_widget(
 			Paragraph::new(Self::content())
-				.style(*STD)
-				.block(block_std())
+				.style(styles.std)
+				.block(block_std(styles))
 				.scroll((self.scroll, 0)),
 			area,
 		)
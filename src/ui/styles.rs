@@ -1,36 +1,78 @@
-use std::sync::{LazyLock, OnceLock};
+use std::{
+	fmt::{self, Display, Formatter},
+	sync::LazyLock,
+};
 
+use clap::ValueEnum;
 use ratatui::{
 	layout::Alignment,
 	style::{Color, Modifier, Style},
 	widgets::{Block, Borders},
 };
 
-pub static STD: LazyLock<Style> =
-	LazyLock::new(|| Style::default().fg(Color::White));
-pub static FOCUS: LazyLock<Style> =
-	LazyLock::new(|| Style::default().fg(Color::LightRed));
-pub static BREAK: LazyLock<Style> =
-	LazyLock::new(|| Style::default().fg(Color::LightBlue));
-pub static ELEM: LazyLock<Style> =
-	LazyLock::new(|| Style::default().fg(Color::Green));
-pub static ELEM_SEL: LazyLock<Style> =
-	LazyLock::new(|| Style::default().fg(Color::Yellow));
-pub static X_GAUGE: LazyLock<Style> =
-	LazyLock::new(|| Style::default().fg(Color::Green).bg(Color::Black));
+/// Colours to draw the interface in.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Theme {
+	/// Light text, for terminals with a dark background
+	#[default]
+	Dark,
+	/// Dark text, for terminals with a light background
+	Light,
+}
+
+impl Theme {
+	pub const fn styles(&self) -> &'static Styles {
+		match self {
+			Theme::Dark => &DARK,
+			Theme::Light => &LIGHT,
+		}
+	}
+}
+
+impl Display for Theme {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match self {
+			Theme::Dark => write!(f, "dark"),
+			Theme::Light => write!(f, "light"),
+		}
+	}
+}
+
+/// The styles of a [`Theme`].
+pub struct Styles {
+	pub std: Style,
+	pub focus: Style,
+	pub brk: Style,
+	pub elem: Style,
+	pub elem_sel: Style,
+	pub x_gauge: Style,
+}
+
+static DARK: Styles = Styles {
+	std: Style::new().fg(Color::White),
+	focus: Style::new().fg(Color::LightRed),
+	brk: Style::new().fg(Color::LightBlue),
+	elem: Style::new().fg(Color::Green),
+	elem_sel: Style::new().fg(Color::Yellow),
+	x_gauge: Style::new().fg(Color::Green).bg(Color::Black),
+};
+
+static LIGHT: Styles = Styles {
+	std: Style::new().fg(Color::Black),
+	focus: Style::new().fg(Color::Red),
+	brk: Style::new().fg(Color::Blue),
+	elem: Style::new().fg(Color::Green),
+	elem_sel: Style::new().fg(Color::Magenta),
+	x_gauge: Style::new().fg(Color::Green).bg(Color::White),
+};
+
 pub static HEADING: LazyLock<Style> = LazyLock::new(|| {
 	Style::default().add_modifier(Modifier::UNDERLINED | Modifier::BOLD)
 });
 
-static BLOCK_STD: OnceLock<Block> = OnceLock::new();
-
-pub fn block_std() -> Block<'static> {
-	BLOCK_STD
-		.get_or_init(|| {
-			Block::default()
-				.borders(Borders::ALL)
-				.style(*STD)
-				.title_alignment(Alignment::Center)
-		})
-		.to_owned()
+pub fn block_std(styles: &Styles) -> Block<'static> {
+	Block::default()
+		.borders(Borders::ALL)
+		.style(styles.std)
+		.title_alignment(Alignment::Center)
 }
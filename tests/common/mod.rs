@@ -0,0 +1,10 @@
+use diesel::{Connection, SqliteConnection};
+use diesel_migrations::MigrationHarness;
+use flussomodoro::db::MIGRATIONS;
+
+/// Connects to a fresh in-memory database with the schema up to date.
+pub fn connect() -> SqliteConnection {
+	let mut conn = SqliteConnection::establish(":memory:").unwrap();
+	conn.run_pending_migrations(MIGRATIONS).unwrap();
+	conn
+}
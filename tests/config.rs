@@ -0,0 +1,258 @@
+mod common;
+
+use std::time::Duration;
+
+use common::connect;
+use diesel::{sql_query, RunQueryDsl, SqliteConnection};
+use flussomodoro::{
+	app::AppOpts,
+	counter::{
+		BreakPolicy,
+		Counter,
+		CounterMode,
+		CounterSnapshot,
+		CounterWorkState,
+		Interruptions,
+		Phase,
+	},
+	db::{ConfigError, ConfigValue, DecodeError},
+	settings::Theme,
+};
+
+/// Saves `data`, given in hex, under `key` as if in `version` of its encoding.
+fn store(conn: &mut SqliteConnection, key: &str, data: &str, version: i32) {
+	sql_query(format!(
+		"REPLACE INTO config (key, data, version) VALUES ('{key}', x'{data}', \
+		 {version})"
+	))
+	.execute(conn)
+	.unwrap();
+}
+
+fn hex(data: &[u8]) -> String {
+	data.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn round_trip<T: ConfigValue + Clone>(
+	conn: &mut SqliteConnection,
+	value: T,
+) -> T {
+	value.set_into(conn, "value".to_string()).unwrap();
+	T::get_from(conn, "value".to_string()).unwrap()
+}
+
+#[test]
+pub fn round_trips() {
+	let mut conn = connect();
+	let conn = &mut conn;
+	assert_eq!(round_trip(conn, "Fokus ⏱️".to_string()), "Fokus ⏱️");
+	assert_eq!(round_trip(conn, 1_500_000u32), 1_500_000);
+	assert_eq!(round_trip(conn, -42i64), -42);
+	assert_eq!(round_trip(conn, 2.5f64), 2.5);
+	assert!(round_trip(conn, true));
+	assert_eq!(
+		round_trip(conn, Duration::new(90, 500)),
+		Duration::new(90, 500)
+	);
+	assert_eq!(
+		round_trip(conn, vec!["To Do".to_string(), "Done".to_string()]),
+		["To Do", "Done"]
+	);
+	assert_eq!(round_trip(conn, Vec::<String>::new()), Vec::<String>::new());
+	assert_eq!(round_trip(conn, Vec::<u32>::new()), Vec::<u32>::new());
+	assert_eq!(round_trip(conn, CounterMode::Classic), CounterMode::Classic);
+	assert_eq!(round_trip(conn, BreakPolicy::Pause), BreakPolicy::Pause);
+	assert_eq!(round_trip(conn, Theme::Light), Theme::Light);
+
+	// enums keep the encoding they had before it was versioned
+	store(conn, "mode", "01", 0);
+	let mode = CounterMode::get_from(conn, "mode".to_string()).unwrap();
+	assert_eq!(mode, CounterMode::Flowtime);
+}
+
+#[test]
+pub fn corrupt_values() {
+	let mut conn = connect();
+	let decode = |err| match err {
+		ConfigError::Decode { source, .. } => source,
+		err => panic!("expected a decode error, got {err}"),
+	};
+
+	assert!(matches!(
+		u16::get_from(&mut conn, "missing".to_string()),
+		Err(ConfigError::NotFound(key)) if key == "missing"
+	));
+	store(&mut conn, "value", "070809", 1);
+	assert_eq!(
+		decode(u16::get_from(&mut conn, "value".to_string()).unwrap_err()),
+		DecodeError::WrongLength { expected: 2, found: 3 }
+	);
+	assert_eq!(
+		decode(
+			CounterSnapshot::get_from(&mut conn, "value".to_string())
+				.unwrap_err()
+		),
+		DecodeError::WrongLength { expected: 46, found: 3 }
+	);
+	store(&mut conn, "value", "07", 1);
+	assert_eq!(
+		decode(bool::get_from(&mut conn, "value".to_string()).unwrap_err()),
+		DecodeError::UnknownVariant { kind: "bool", value: 7 }
+	);
+	assert_eq!(
+		decode(
+			BreakPolicy::get_from(&mut conn, "value".to_string()).unwrap_err()
+		),
+		DecodeError::UnknownVariant { kind: "break policy", value: 7 }
+	);
+	store(&mut conn, "value", "ffffffffffffffff3b9aca00", 1);
+	assert_eq!(
+		decode(Duration::get_from(&mut conn, "value".to_string()).unwrap_err()),
+		DecodeError::OutOfRange { kind: "nanoseconds" }
+	);
+	store(&mut conn, "value", "ff", 1);
+	assert_eq!(
+		decode(String::get_from(&mut conn, "value".to_string()).unwrap_err()),
+		DecodeError::InvalidUtf8
+	);
+
+	// settings that cannot be read back fall back to their defaults
+	store(&mut conn, "focus_time", "07", 1);
+	store(&mut conn, "notify", "07", 1);
+	store(&mut conn, "short_break", "0000012c", 1);
+	let mut opts = AppOpts::default();
+	opts.load_config(&mut conn, &AppOpts::default()).unwrap();
+	assert_eq!(opts.focus_time, None);
+	assert_eq!(opts.notify, None);
+	assert_eq!(opts.short_break, Some(300));
+}
+
+#[test]
+pub fn out_of_range() {
+	let mut conn = connect();
+	let load = |conn: &mut _| {
+		let mut opts = AppOpts::default();
+		opts.load_config(conn, &AppOpts::default()).unwrap();
+		opts
+	};
+
+	// if a saved setting would not be taken on the command line then it falls
+	// back to its default, like any other that cannot be read back
+	store(&mut conn, "clover_length", "00", 1);
+	store(&mut conn, "focus_time", "00000000", 1);
+	store(&mut conn, "break_ratio", "0000000000000000", 1);
+	let opts = load(&mut conn);
+	assert_eq!(opts.clover_length, None);
+	assert_eq!(opts.focus_time, None);
+	assert_eq!(opts.break_ratio, None);
+	let mut counter = Counter::with_opts(&opts);
+	counter.reconfigure(&opts);
+	assert_eq!(counter.clover_length(), 4);
+
+	store(&mut conn, "break_ratio", "7ff8000000000000", 1);
+	assert_eq!(load(&mut conn).break_ratio, None);
+	store(&mut conn, "break_ratio", "4004000000000000", 1);
+	store(&mut conn, "clover_length", "02", 1);
+	let opts = load(&mut conn);
+	assert_eq!(opts.break_ratio, Some(2.5));
+	assert_eq!(opts.clover_length, Some(2));
+}
+
+#[test]
+pub fn versions() {
+	let mut conn = connect();
+
+	// durations saved before they were widened from u16
+	store(&mut conn, "focus_time", "0708", 0);
+	let focus_time = u32::get_from(&mut conn, "focus_time".to_string());
+	assert_eq!(focus_time.unwrap(), 1800);
+
+	// values saved by a newer release are not guessed at
+	store(&mut conn, "focus_time", "00000708", 2);
+	assert!(matches!(
+		u32::get_from(&mut conn, "focus_time".to_string()),
+		Err(ConfigError::UnsupportedVersion { version: 2, .. })
+	));
+	store(&mut conn, "focus_time", "00000708", -1);
+	assert!(matches!(
+		u32::get_from(&mut conn, "focus_time".to_string()),
+		Err(ConfigError::UnsupportedVersion { version: -1, .. })
+	));
+	// and saving over them writes the version that is understood
+	1800u32.set_into(&mut conn, "focus_time".to_string()).unwrap();
+	let focus_time = u32::get_from(&mut conn, "focus_time".to_string());
+	assert_eq!(focus_time.unwrap(), 1800);
+}
+
+#[test]
+pub fn snapshots() {
+	let mut conn = connect();
+	let snapshot = CounterSnapshot {
+		mode: Some(CounterMode::Flowtime),
+		focus_time: 1200,
+		break_time: 60,
+		overdraft: 0,
+		pom: 2,
+		work_state: CounterWorkState::Paused { previous: Phase::Break },
+		session_start: Some(1_000_000),
+		interruptions: Interruptions { internal: 1, external: 2 },
+		break_start: None,
+		break_taken: 30,
+		timestamp: 1_000_300,
+	};
+	assert_eq!(round_trip(&mut conn, snapshot), snapshot);
+	let key = || "value".to_string();
+	let encoded = snapshot.encode();
+
+	// snapshots saved before encodings were versioned have the first layout,
+	// which left out the mode
+	let unknown = CounterSnapshot { mode: None, ..snapshot };
+	store(&mut conn, "value", &hex(&encoded[..46]), 0);
+	assert_eq!(CounterSnapshot::get_from(&mut conn, key()).unwrap(), unknown);
+	store(&mut conn, "value", &hex(&encoded[..46]), 1);
+	assert_eq!(CounterSnapshot::get_from(&mut conn, key()).unwrap(), unknown);
+	assert_eq!(round_trip(&mut conn, unknown), unknown);
+
+	// if the layout is not the one versioned then it is not guessed at
+	store(&mut conn, "value", &hex(&encoded[..22]), 0);
+	assert!(matches!(
+		CounterSnapshot::get_from(&mut conn, key()),
+		Err(ConfigError::Decode {
+			source: DecodeError::WrongLength { expected: 46, found: 22 },
+			..
+		})
+	));
+	store(&mut conn, "value", &hex(&encoded[..46]), 2);
+	assert!(matches!(
+		CounterSnapshot::get_from(&mut conn, key()),
+		Err(ConfigError::Decode {
+			source: DecodeError::WrongLength { expected: 47, found: 46 },
+			..
+		})
+	));
+	let mut encoded = encoded;
+	encoded[46] = 7;
+	store(&mut conn, "value", &hex(&encoded), 2);
+	assert!(matches!(
+		CounterSnapshot::get_from(&mut conn, key()),
+		Err(ConfigError::Decode {
+			source: DecodeError::UnknownVariant {
+				kind: "counter mode",
+				value: 7
+			},
+			..
+		})
+	));
+	encoded[13] = 9;
+	store(&mut conn, "value", &hex(&encoded[..46]), 1);
+	assert!(matches!(
+		CounterSnapshot::get_from(&mut conn, key()),
+		Err(ConfigError::Decode {
+			source: DecodeError::UnknownVariant {
+				kind: "work state",
+				value: 9
+			},
+			..
+		})
+	));
+}
@@ -1,120 +1,1057 @@
-use flussomodoro::counter::{Counter, CounterWorkState};
+mod common;
+
+use std::{
+	cell::RefCell,
+	time::{Duration, UNIX_EPOCH},
+};
+
+use common::connect;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use flussomodoro::{
+	app::{App, AppNotification, AppOpts},
+	clock::{Clock, MockClock},
+	counter::{
+		BreakPolicy,
+		Counter,
+		CounterEvent,
+		CounterMode,
+		CounterSnapshot,
+		CounterWorkState,
+		InterruptionKind,
+		Interruptions,
+		Phase,
+		PhaseOutcome,
+		PhaseSummary,
+		Transition,
+	},
+};
+
+/// Notifications over a whole focus session with the default settings.
+const SESSION: [AppNotification; 3] = [
+	AppNotification::FocusHalfway,
+	AppNotification::FocusAlmostOver(5 * 60),
+	AppNotification::PomComplete,
+];
+
+/// Drives a counter with a mock clock, recording its notifications.
+struct Harness {
+	counter: Counter,
+	clock: MockClock,
+	notifications: RefCell<Vec<AppNotification>>,
+	transitions: RefCell<Vec<Transition>>,
+	phases: RefCell<Vec<PhaseSummary>>,
+}
+
+impl Harness {
+	fn new(mut counter: Counter) -> Self {
+		let clock = MockClock::new(UNIX_EPOCH + Duration::from_secs(1_000_000));
+		counter.tick(&clock, |_| ());
+		Harness {
+			counter,
+			clock,
+			notifications: RefCell::new(Vec::new()),
+			transitions: RefCell::new(Vec::new()),
+			phases: RefCell::new(Vec::new()),
+		}
+	}
+
+	fn advance(&mut self, secs: u64) {
+		self.advance_by(Duration::from_secs(secs));
+	}
+
+	fn advance_by(&mut self, duration: Duration) {
+		self.clock.advance(duration);
+		self.counter.tick(&self.clock, |event| match event {
+			CounterEvent::Notify(msg) => {
+				self.notifications.borrow_mut().push(msg)
+			}
+			CounterEvent::Transition(transition) => {
+				self.transitions.borrow_mut().push(transition)
+			}
+			CounterEvent::PhaseEnded(summary) => {
+				self.phases.borrow_mut().push(summary)
+			}
+		});
+	}
+
+	fn take_notifications(&self) -> Vec<AppNotification> {
+		self.notifications.take()
+	}
+
+	fn take_transitions(&self) -> Vec<Transition> {
+		self.transitions.take()
+	}
+
+	/// Phases ended since last taken, with their wall-clock and counted
+	/// lengths.
+	fn take_phases(&self) -> Vec<(Phase, PhaseOutcome, u64, u32)> {
+		self.phases
+			.take()
+			.into_iter()
+			.map(|summary| {
+				let length = summary
+					.ended
+					.unwrap()
+					.duration_since(summary.started.unwrap())
+					.unwrap();
+				let length = length.as_secs();
+				(summary.phase, summary.outcome, length, summary.counted)
+			})
+			.collect()
+	}
+}
 
 #[test]
 pub fn counter_work_state_access() {
-	// if init then work state should be none
+	// if init then work state should be idle
 	let mut test_counter = Counter::new();
-	assert_eq!(test_counter.work_state().into_inner(), None);
+	assert_eq!(test_counter.work_state(), CounterWorkState::Idle);
 	assert!(!test_counter.work_state().is_active());
 	assert!(!test_counter.work_state().is_focusing());
 
 	// if started then work state should be active
-	test_counter.start();
-	assert_eq!(test_counter.work_state().into_inner(), Some(true));
+	assert_eq!(
+		test_counter.start(),
+		Some(Transition {
+			from: CounterWorkState::Idle,
+			to: CounterWorkState::Focusing
+		})
+	);
+	assert_eq!(test_counter.work_state(), CounterWorkState::Focusing);
 	assert!(test_counter.work_state().is_active());
 	assert!(test_counter.work_state().is_focusing());
 
-	// if reset then work state should be none
-	test_counter.reset();
-	assert_eq!(test_counter.work_state().into_inner(), None);
+	// if already started then starting again should not transition
+	assert_eq!(test_counter.start(), None);
+
+	// if reset then work state should be idle
+	assert_eq!(
+		test_counter.reset(),
+		Some(Transition {
+			from: CounterWorkState::Focusing,
+			to: CounterWorkState::Idle
+		})
+	);
+	assert_eq!(test_counter.work_state(), CounterWorkState::Idle);
 	assert!(!test_counter.work_state().is_active());
 	assert!(!test_counter.work_state().is_focusing());
 }
 
 #[test]
 pub fn work_state_impl() {
-	let mut test_work_state = CounterWorkState::from(None);
+	let mut test_work_state = CounterWorkState::default();
+	let paused_focus = CounterWorkState::Paused { previous: Phase::Focus };
+	let paused_break = CounterWorkState::Paused { previous: Phase::Break };
 
-	// if none and break toggled then work state should be none
+	// if idle and break toggled then work state should be idle
 	test_work_state.toggle_break();
-	assert_eq!(test_work_state.into_inner(), None);
+	assert_eq!(test_work_state, CounterWorkState::Idle);
 	assert!(!test_work_state.is_active());
 	assert!(!test_work_state.is_focusing());
+	assert_eq!(test_work_state.phase(), None);
 
-	// if none and active toggled then work state should be active
+	// if idle and active toggled then work state should be focusing
 	test_work_state.toggle_active();
-	assert_eq!(test_work_state.into_inner(), Some(true));
+	assert_eq!(test_work_state, CounterWorkState::Focusing);
 	assert!(test_work_state.is_active());
 	assert!(test_work_state.is_focusing());
+	assert_eq!(test_work_state.phase(), Some(Phase::Focus));
 
-	// if active and break toggled then work state should be break
+	// if focusing and break toggled then work state should be break
 	test_work_state.toggle_break();
-	assert_eq!(test_work_state.into_inner(), Some(false));
+	assert_eq!(test_work_state, CounterWorkState::OnBreak);
 	assert!(test_work_state.is_active());
 	assert!(!test_work_state.is_focusing());
+	assert!(test_work_state.is_on_break());
+	assert_eq!(test_work_state.phase(), Some(Phase::Break));
 
-	// if break and break toggled then work state should be active
+	// if break and break toggled then work state should be focusing
 	test_work_state.toggle_break();
-	assert_eq!(test_work_state.into_inner(), Some(true));
+	assert_eq!(test_work_state, CounterWorkState::Focusing);
 
-	// if focus and active toggled then work state should be none
-	assert_eq!(test_work_state.into_inner(), Some(true));
+	// if focusing and active toggled then work state should be paused focus
 	test_work_state.toggle_active();
-	assert_eq!(test_work_state.into_inner(), None);
+	assert_eq!(test_work_state, paused_focus);
+	assert!(!test_work_state.is_active());
+	assert!(test_work_state.is_paused());
+	assert_eq!(test_work_state.phase(), Some(Phase::Focus));
+
+	// if paused and break toggled then work state should not change
+	test_work_state.toggle_break();
+	assert_eq!(test_work_state, paused_focus);
 
-	// if break and active toggled then work state should be none
+	// if paused focus and active toggled then resume focusing
 	test_work_state.toggle_active();
+	assert_eq!(test_work_state, CounterWorkState::Focusing);
+
+	// if break and active toggled then work state should be paused break
 	test_work_state.toggle_break();
-	assert_eq!(test_work_state.into_inner(), Some(false));
 	test_work_state.toggle_active();
-	assert_eq!(test_work_state.into_inner(), None);
+	assert_eq!(test_work_state, paused_break);
+	assert_eq!(test_work_state.phase(), Some(Phase::Break));
+
+	// if paused break and active toggled then resume the break
+	test_work_state.toggle_active();
+	assert_eq!(test_work_state, CounterWorkState::OnBreak);
+}
+
+#[test]
+pub fn work_state_labels() {
+	assert_eq!(CounterWorkState::Idle.to_string(), "Idle");
+	assert_eq!(CounterWorkState::Focusing.to_string(), "Focus");
+	assert_eq!(CounterWorkState::OnBreak.to_string(), "Break");
+	assert_eq!(
+		CounterWorkState::Paused { previous: Phase::Focus }.to_string(),
+		"Paused (focus)"
+	);
+	assert_eq!(
+		CounterWorkState::Paused { previous: Phase::Break }.to_string(),
+		"Paused (break)"
+	);
 }
 
 #[test]
 pub fn counter_work_state_functionality() {
-	let mut test_counter = Counter::new();
+	let mut harness = Harness::new(Counter::new());
 
 	// if not active then work should do nothing
-	(0..5).for_each(|_| test_counter.work(|_| ()));
-	assert_eq!(test_counter.focus_time(), 25 * 60);
+	harness.advance(5);
+	assert_eq!(harness.counter.focus_time(), 25 * 60);
 
 	// if active then work should work
-	test_counter.work_state_mut().toggle_active();
-	(0..5).for_each(|_| test_counter.work(|_| ()));
-	assert_eq!(test_counter.focus_time(), 25 * 60 - 5);
-	assert_eq!(test_counter.break_time(), 1);
+	harness.counter.toggle_active();
+	harness.advance(5);
+	assert_eq!(harness.counter.focus_time(), 25 * 60 - 5);
+	assert_eq!(harness.counter.break_time(), 1);
 
 	// if active then work should continue to work linearly
-	(0..20).for_each(|_| test_counter.work(|_| ()));
-	assert_eq!(test_counter.focus_time(), 25 * 60 - 25);
-	assert_eq!(test_counter.break_time(), 5);
+	harness.advance(20);
+	assert_eq!(harness.counter.focus_time(), 25 * 60 - 25);
+	assert_eq!(harness.counter.break_time(), 5);
 
 	// if break then work should decrement break and retain focus
-	test_counter.work_state_mut().toggle_break();
-	test_counter.work(|_| ());
-	assert_eq!(test_counter.focus_time(), 25 * 60 - 25);
-	assert_eq!(test_counter.break_time(), 4);
+	harness.counter.toggle_break(|_| ());
+	harness.advance(1);
+	assert_eq!(harness.counter.focus_time(), 25 * 60 - 25);
+	assert_eq!(harness.counter.break_time(), 4);
+
+	// if paused during break then resuming should continue the break
+	harness.counter.toggle_active();
+	harness.advance(60);
+	harness.counter.toggle_active();
+	assert_eq!(harness.counter.work_state(), CounterWorkState::OnBreak);
+	assert_eq!(harness.counter.break_time(), 4);
 
 	// if break empty then work should reset counter
-	(0..5).for_each(|_| test_counter.work(|_| ()));
-	assert_eq!(test_counter.work_state().into_inner(), None);
-	assert_eq!(test_counter.focus_time(), 25 * 60);
-	assert_eq!(test_counter.break_time(), 0);
+	harness.advance(5);
+	assert_eq!(harness.counter.work_state(), CounterWorkState::Idle);
+	assert_eq!(harness.counter.focus_time(), 25 * 60);
+	assert_eq!(harness.counter.break_time(), 0);
+	assert_eq!(harness.take_notifications(), [AppNotification::BreakOver(
+		BreakPolicy::Reset
+	)]);
+	assert_eq!(harness.take_transitions(), [Transition {
+		from: CounterWorkState::OnBreak,
+		to: CounterWorkState::Idle
+	}]);
 }
 
 #[test]
 pub fn session_rollover() {
-	let mut test_counter = Counter::new();
-	assert_eq!(test_counter.pom(), 1);
+	let mut harness = Harness::new(Counter::new());
+	assert_eq!(harness.counter.pom(), 1);
 
 	// if active and focus session finished then move to next session
-	test_counter.start();
-	(0..25 * 60).for_each(|_| test_counter.work(|_| ()));
-	assert_eq!(test_counter.work_state().into_inner(), None);
-	assert_eq!(test_counter.focus_time(), 25 * 60);
-	assert_eq!(test_counter.break_time(), 25 * 60 / 5);
-	assert_eq!(test_counter.pom(), 2);
+	harness.counter.start();
+	harness.advance(25 * 60);
+	assert_eq!(harness.counter.work_state(), CounterWorkState::Idle);
+	assert_eq!(harness.counter.focus_time(), 25 * 60);
+	assert_eq!(harness.counter.break_time(), 25 * 60 / 5);
+	assert_eq!(harness.counter.pom(), 2);
+	assert_eq!(harness.take_notifications(), SESSION);
 
 	// if active and 4 focus sessions finished then clover completed
 	// if clover completed give break bonus
+	for _ in 2..=4 {
+		harness.counter.start();
+		harness.advance(25 * 60);
+	}
+	assert_eq!(harness.counter.work_state(), CounterWorkState::Idle);
+	assert_eq!(harness.counter.focus_time(), 25 * 60);
+	assert_eq!(harness.counter.break_time(), 25 * 60 / 5 * 4 + 15 * 60); // 4 sessions of break + clover bonus
+	assert_eq!(harness.counter.pom(), 1); // back to 1st pom of a clover
+	assert_eq!(
+		harness.take_notifications(),
+		[&SESSION[..], &SESSION, &SESSION, &[AppNotification::CloverComplete]]
+			.concat()
+	);
+}
+
+#[test]
+pub fn snapshot_restore() {
+	let then = UNIX_EPOCH + Duration::from_secs(1_000_000);
+	let mut test_counter = Counter::new();
 	test_counter.start();
-	(0..25 * 60).for_each(|_| test_counter.work(|_| ())); // 2nd
-	test_counter.start();
-	(0..25 * 60).for_each(|_| test_counter.work(|_| ())); // 3rd
-	test_counter.start();
-	(0..25 * 60).for_each(|_| test_counter.work(|_| ())); // 4th
-	assert_eq!(test_counter.work_state().into_inner(), None);
-	assert_eq!(test_counter.focus_time(), 25 * 60);
-	assert_eq!(test_counter.break_time(), 25 * 60 / 5 * 4 + 15 * 60); // 4 sessions of break + clover bonus
-	assert_eq!(test_counter.pom(), 1); // back to 1st pom of a clover
+	(0..25).for_each(|_| test_counter.work(|_| ()));
+	let snapshot = test_counter.snapshot(then);
+	assert_eq!(snapshot.timestamp, 1_000_000);
+
+	// if focusing when saved then restore paused with progress intact
+	let mut restored = Counter::new();
+	restored.restore(snapshot, then + Duration::from_secs(3600), |_| ());
+	assert_eq!(restored.work_state(), CounterWorkState::Paused {
+		previous: Phase::Focus
+	});
+	assert_eq!(restored.focus_time(), 25 * 60 - 25);
+	assert_eq!(restored.break_time(), 5);
+	assert_eq!(restored.pom(), 1);
+
+	// if on break when saved then break should drain while away
+	test_counter.toggle_break(|_| ());
+	let snapshot = test_counter.snapshot(then);
+	let mut restored = Counter::new();
+	restored.restore(snapshot, then + Duration::from_secs(2), |_| ());
+	assert_eq!(restored.work_state(), CounterWorkState::OnBreak);
+	assert_eq!(restored.break_time(), 3);
+
+	// if away for longer than the break then the session should reset, and
+	// the break and session should end as they would have if not away
+	let snapshot = CounterSnapshot { session_start: Some(999_000), ..snapshot };
+	let mut restored = Counter::new();
+	let mut events = Vec::new();
+	restored.restore(snapshot, then + Duration::from_secs(3600), |event| {
+		events.push(event)
+	});
+	assert_eq!(restored.work_state(), CounterWorkState::Idle);
+	assert_eq!(restored.focus_time(), 25 * 60);
+	assert_eq!(restored.break_time(), 0);
+	assert_eq!(phases_ended(&events), [
+		(Phase::Break, PhaseOutcome::Completed),
+		(Phase::Focus, PhaseOutcome::Interrupted),
+	]);
+}
+
+#[test]
+pub fn snapshot_mode_change() {
+	let then = UNIX_EPOCH + Duration::from_secs(1_000_000);
+	let flowtime =
+		AppOpts { mode: Some(CounterMode::Flowtime), ..Default::default() };
+	let mut counter = Counter::with_opts(&flowtime);
+	counter.start();
+	(0..600).for_each(|_| counter.work(|_| ()));
+	assert_eq!(counter.focus_time(), 600);
+
+	// if the mode changed while away then the focus so far is kept, counting
+	// down instead of up, like when reconfigured
+	let mut restored = Counter::new();
+	restored.restore(counter.snapshot(then), then, |_| ());
+	assert_eq!(restored.focused(), 600);
+	assert_eq!(restored.focus_time(), 25 * 60 - 600);
+
+	// and the other way around
+	let mut restored_flowtime = Counter::with_opts(&flowtime);
+	restored_flowtime.restore(restored.snapshot(then), then, |_| ());
+	assert_eq!(restored_flowtime.focus_time(), 600);
+
+	// if the mode it was saved in is unknown then it is taken to be the same
+	let snapshot = CounterSnapshot { mode: None, ..counter.snapshot(then) };
+	let mut restored = Counter::with_opts(&flowtime);
+	restored.restore(snapshot, then, |_| ());
+	assert_eq!(restored.focus_time(), 600);
+}
+
+#[test]
+pub fn break_ratio() {
+	// if ratio is whole then break should be earned in whole steps
+	let mut harness = Harness::new(Counter::with_opts(&AppOpts {
+		break_ratio: Some(3.0),
+		..Default::default()
+	}));
+	assert_eq!(harness.counter.break_ratio(), 3.0);
+	harness.counter.start();
+	harness.advance(2);
+	assert_eq!(harness.counter.break_time(), 0);
+	harness.advance(1);
+	assert_eq!(harness.counter.break_time(), 1);
+	harness.advance(25 * 60 - 3);
+	assert_eq!(harness.counter.break_time(), 25 * 60 / 3);
+
+	// if ratio is fractional then break should be earned proportionally
+	let mut harness = Harness::new(Counter::with_opts(&AppOpts {
+		break_ratio: Some(2.5),
+		..Default::default()
+	}));
+	harness.counter.start();
+	harness.advance(2);
+	assert_eq!(harness.counter.break_time(), 0);
+	harness.advance(3);
+	assert_eq!(harness.counter.break_time(), 2);
+	harness.advance(25 * 60 - 5);
+	assert_eq!(harness.counter.break_time(), 25 * 60 * 2 / 5);
+}
+
+#[test]
+pub fn clover_length() {
+	let mut harness = Harness::new(Counter::with_opts(&AppOpts {
+		focus_time: Some(10),
+		clover_length: Some(3),
+		..Default::default()
+	}));
+	assert_eq!(harness.counter.clover_length(), 3);
+
+	// if clover length reached then clover completed, by the last session
+	for pom in 1..=3 {
+		assert_eq!(harness.counter.pom(), pom);
+		harness.counter.start();
+		harness.advance(10);
+	}
+	assert_eq!(harness.counter.pom(), 1);
+	let clovers: Vec<bool> =
+		harness.phases.take().iter().map(|summary| summary.clover).collect();
+	assert_eq!(clovers, [false, false, true]);
+	assert_eq!(harness.counter.break_time(), 10 / 5 * 3 + 15 * 60);
+}
+
+#[test]
+pub fn wall_clock_ticks() {
+	let mut harness = Harness::new(Counter::new());
+	harness.counter.start();
+
+	// if less than a second elapsed then carry the remainder over
+	harness.advance_by(Duration::from_millis(1500));
+	assert_eq!(harness.counter.focus_time(), 25 * 60 - 1);
+	harness.advance_by(Duration::from_millis(500));
+	assert_eq!(harness.counter.focus_time(), 25 * 60 - 2);
+
+	// if the clock went backwards then count from the new time
+	let now = harness.clock.now();
+	harness.clock.set(now - Duration::from_secs(60));
+	harness.advance(0);
+	harness.advance(1);
+	assert_eq!(harness.counter.focus_time(), 25 * 60 - 3);
+
+	// if paused then elapsed time should not count
+	harness.counter.toggle_active();
+	harness.advance(600);
+	harness.counter.toggle_active();
+	harness.advance(1);
+	assert_eq!(harness.counter.focus_time(), 25 * 60 - 4);
+}
+
+#[test]
+pub fn suspend_catch_up() {
+	let mut harness = Harness::new(Counter::new());
+	harness.counter.start();
+
+	// if suspended past the end of a session then complete it
+	harness.advance(3600);
+	assert_eq!(harness.take_notifications(), SESSION);
+	assert_eq!(harness.counter.work_state(), CounterWorkState::Idle);
+	assert_eq!(harness.counter.pom(), 2);
+	assert_eq!(harness.counter.break_time(), 25 * 60 / 5);
+
+	// if suspended past the end of a break then fire every warning
+	harness.counter.start();
+	harness.counter.toggle_break(|_| ());
+	harness.advance(3600);
+	assert_eq!(harness.take_notifications(), [
+		AppNotification::BreakAlmostOver(30),
+		AppNotification::BreakOver(BreakPolicy::Reset)
+	]);
+	assert_eq!(harness.counter.break_time(), 0);
+}
+
+#[test]
+pub fn full_day() {
+	let mut harness = Harness::new(Counter::new());
+
+	// if working clovers back to back and spending their break then every
+	// clover should play out the same
+	for _ in 0..4 {
+		for _ in 0..4 {
+			harness.counter.start();
+			harness.advance(25 * 60);
+		}
+		assert_eq!(harness.counter.break_time(), 25 * 60 / 5 * 4 + 15 * 60);
+		harness.counter.start();
+		harness.counter.toggle_break(|_| ());
+		harness.advance(3600);
+		assert_eq!(harness.counter.break_time(), 0);
+		assert_eq!(harness.counter.pom(), 1);
+		assert_eq!(
+			harness.take_notifications(),
+			[&SESSION[..], &SESSION, &SESSION, &SESSION, &[
+				AppNotification::CloverComplete,
+				AppNotification::BreakAlmostOver(30),
+				AppNotification::BreakOver(BreakPolicy::Reset),
+			]]
+			.concat()
+		);
+	}
+
+	// if idle for the rest of the day then nothing should happen
+	harness.advance(24 * 3600);
+	assert_eq!(harness.take_notifications(), []);
+	assert_eq!(harness.counter.focus_time(), 25 * 60);
+}
+
+fn with_break_policy(break_policy: BreakPolicy) -> Harness {
+	let mut harness = Harness::new(Counter::with_opts(&AppOpts {
+		break_policy: Some(break_policy),
+		..Default::default()
+	}));
+	harness.counter.start();
+	harness.advance(50);
+	harness.counter.toggle_break(|_| ());
+	harness
+}
+
+#[test]
+pub fn break_policies() {
+	// if resume policy then go back to focus and keep progress
+	let mut harness = with_break_policy(BreakPolicy::Resume);
+	harness.advance(15);
+	assert_eq!(harness.take_notifications(), [AppNotification::BreakOver(
+		BreakPolicy::Resume
+	)]);
+	assert_eq!(harness.counter.work_state(), CounterWorkState::Focusing);
+	assert_eq!(harness.counter.focus_time(), 25 * 60 - 55);
+	assert_eq!(harness.counter.break_time(), 1);
+
+	// if pause policy then wait to resume focus and keep progress
+	let mut harness = with_break_policy(BreakPolicy::Pause);
+	harness.advance(15);
+	assert_eq!(harness.take_notifications(), [AppNotification::BreakOver(
+		BreakPolicy::Pause
+	)]);
+	assert_eq!(harness.counter.work_state(), CounterWorkState::Paused {
+		previous: Phase::Focus
+	});
+	assert_eq!(harness.counter.focus_time(), 25 * 60 - 50);
+	harness.counter.toggle_active();
+	assert_eq!(harness.counter.work_state(), CounterWorkState::Focusing);
+
+	// if overdraft policy then keep the break going into the negative
+	let mut harness = with_break_policy(BreakPolicy::Overdraft);
+	harness.advance(15);
+	assert_eq!(harness.take_notifications(), [AppNotification::BreakOver(
+		BreakPolicy::Overdraft
+	)]);
+	assert_eq!(harness.counter.work_state(), CounterWorkState::OnBreak);
+	assert_eq!(harness.counter.break_time(), 0);
+	assert_eq!(harness.counter.overdraft(), 5);
+
+	// if overdrawn then earned break should repay the overdraft first
+	harness.counter.toggle_break(|_| ());
+	harness.advance(30);
+	assert_eq!(harness.counter.overdraft(), 0);
+	assert_eq!(harness.counter.break_time(), 1);
+	assert_eq!(harness.take_notifications(), []);
+}
+
+#[test]
+pub fn empty_break() {
+	// if a break is started without any break then it ends immediately
+	let mut harness = Harness::new(Counter::new());
+	harness.counter.start();
+	harness.counter.toggle_break(|_| ());
+	harness.advance(1);
+	assert_eq!(harness.counter.work_state(), CounterWorkState::Idle);
+	assert_eq!(harness.counter.break_time(), 0);
+	assert_eq!(harness.take_notifications(), [AppNotification::BreakOver(
+		BreakPolicy::Reset
+	)]);
+}
+
+#[test]
+pub fn warnings() {
+	let mut harness = Harness::new(Counter::with_opts(&AppOpts {
+		focus_time: Some(20 * 60),
+		break_warnings: Some(vec![5 * 60, 60, 10, 0]),
+		focus_warnings: Some(vec![60]),
+		..Default::default()
+	}));
+	assert_eq!(harness.counter.break_warnings(), [5 * 60, 60, 10]);
+
+	// if focusing then fire milestones as they are reached
+	harness.counter.start();
+	harness.advance(10 * 60);
+	assert_eq!(harness.take_notifications(), [AppNotification::FocusHalfway]);
+	harness.advance(9 * 60);
+	assert_eq!(harness.take_notifications(), [
+		AppNotification::FocusAlmostOver(60)
+	]);
+	harness.advance(60);
+	assert_eq!(harness.take_notifications(), [AppNotification::PomComplete]);
+
+	// if on break then fire each warning in turn
+	harness.counter.start();
+	harness.counter.toggle_break(|_| ());
+	assert_eq!(harness.counter.break_time(), 4 * 60);
+	harness.advance(3 * 60);
+	assert_eq!(harness.take_notifications(), [
+		AppNotification::BreakAlmostOver(60)
+	]);
+	harness.advance(60);
+	assert_eq!(harness.take_notifications(), [
+		AppNotification::BreakAlmostOver(10),
+		AppNotification::BreakOver(BreakPolicy::Reset),
+	]);
+
+	// if no warnings are wanted then only the end of the phase is notified
+	let mut harness = Harness::new(Counter::with_opts(&AppOpts {
+		break_warnings: Some(vec![]),
+		..Default::default()
+	}));
+	harness.counter.start();
+	harness.advance(5 * 60);
+	harness.counter.toggle_break(|_| ());
+	harness.advance(60);
+	assert_eq!(harness.take_notifications(), [AppNotification::BreakOver(
+		BreakPolicy::Reset
+	)]);
+}
+
+#[test]
+pub fn flowtime() {
+	let mut harness = Harness::new(Counter::with_opts(&AppOpts {
+		mode: Some(CounterMode::Flowtime),
+		flow_min: Some(10 * 60),
+		..Default::default()
+	}));
+	assert_eq!(harness.counter.focus_time(), 0);
+
+	// if flowing then focus should count up and earn break with no end
+	harness.counter.start();
+	harness.advance(3 * 3600);
+	assert_eq!(harness.counter.focus_time(), 3 * 3600);
+	assert_eq!(harness.counter.focused(), 3 * 3600);
+	assert_eq!(harness.counter.break_time(), 3 * 3600 / 5);
+	assert_eq!(harness.counter.work_state(), CounterWorkState::Focusing);
+	assert_eq!(harness.take_notifications(), []);
+
+	// if ended after the minimum length then count it as a pom
+	harness.counter.end_session(|_| ());
+	assert_eq!(harness.counter.pom(), 2);
+	assert_eq!(harness.counter.focus_time(), 0);
+	assert_eq!(harness.counter.work_state(), CounterWorkState::Idle);
+
+	// if ended before the minimum length then discard it
+	harness.counter.start();
+	harness.advance(5 * 60);
+	harness.counter.toggle_active();
+	harness.counter.end_session(|_| ());
+	assert_eq!(harness.counter.pom(), 2);
+	assert_eq!(harness.counter.focus_time(), 0);
+	assert_eq!(harness.counter.work_state(), CounterWorkState::Idle);
+
+	// if not in flowtime mode then sessions cannot be ended early
+	let mut harness = Harness::new(Counter::new());
+	harness.counter.start();
+	harness.advance(20 * 60);
+	harness.counter.end_session(|_| ());
+	assert_eq!(harness.counter.pom(), 1);
+	assert_eq!(harness.counter.work_state(), CounterWorkState::Focusing);
+}
+
+#[test]
+pub fn classic() {
+	let mut harness = Harness::new(Counter::with_opts(&AppOpts {
+		mode: Some(CounterMode::Classic),
+		focus_time: Some(25 * 60),
+		short_break: Some(5 * 60),
+		long_break: Some(15 * 60),
+		..Default::default()
+	}));
+
+	// if a session is completed then start a short break with no break earned
+	harness.counter.start();
+	harness.advance(25 * 60);
+	assert_eq!(harness.take_notifications(), SESSION);
+	assert_eq!(harness.counter.work_state(), CounterWorkState::OnBreak);
+	assert_eq!(harness.counter.break_time(), 5 * 60);
+	assert_eq!(harness.counter.pom(), 2);
+
+	// if the break is over then wait for the next session, keeping progress
+	harness.advance(3600);
+	assert_eq!(harness.take_notifications(), [
+		AppNotification::BreakAlmostOver(30),
+		AppNotification::BreakOver(BreakPolicy::Pause),
+	]);
+	assert_eq!(harness.counter.work_state(), CounterWorkState::Paused {
+		previous: Phase::Focus
+	});
+	assert_eq!(harness.counter.break_time(), 0);
+	assert_eq!(harness.counter.focus_time(), 25 * 60);
+	assert_eq!(harness.counter.pom(), 2);
+
+	// if a clover is completed then start a long break instead
+	harness.counter.toggle_active();
+	for pom in 2..=4 {
+		harness.advance(25 * 60);
+		if pom < 4 {
+			// skip the short break
+			harness.counter.toggle_break(|_| ());
+		}
+	}
+	assert_eq!(harness.counter.pom(), 1);
+	assert_eq!(harness.counter.work_state(), CounterWorkState::OnBreak);
+	assert_eq!(harness.counter.break_time(), 15 * 60);
+	assert_eq!(harness.counter.break_policy(), BreakPolicy::Pause);
+}
+
+#[test]
+pub fn long_durations() {
+	// if durations exceed what fits in a u16 then they should still count
+	let mut harness = Harness::new(Counter::with_opts(&AppOpts {
+		focus_time: Some(20 * 3600),
+		clover_break_bonus: Some(20 * 3600),
+		clover_length: Some(1),
+		..Default::default()
+	}));
+	harness.counter.start();
+	harness.advance(10 * 3600);
+	assert_eq!(harness.counter.focus_time(), 10 * 3600);
+	harness.advance(10 * 3600);
+	assert_eq!(harness.counter.pom(), 1);
+	assert_eq!(harness.counter.break_time(), 20 * 3600 / 5 + 20 * 3600);
+}
+
+#[test]
+pub fn abandon_skip_rewind() {
+	let mut harness = Harness::new(Counter::new());
+	let mut events = Vec::new();
+
+	// if a session is abandoned then discard its progress but keep its break
+	harness.counter.start();
+	harness.advance(10 * 60);
+	harness.counter.abandon(|event| events.push(event));
+	let start = harness.clock.now() - Duration::from_secs(10 * 60);
+	assert_eq!(events, [
+		CounterEvent::PhaseEnded(PhaseSummary {
+			phase: Phase::Focus,
+			outcome: PhaseOutcome::Interrupted,
+			started: Some(start),
+			ended: Some(harness.clock.now()),
+			counted: 10 * 60,
+			interruptions: Interruptions::default(),
+			clover: false,
+		}),
+		CounterEvent::Transition(Transition {
+			from: CounterWorkState::Focusing,
+			to: CounterWorkState::Idle
+		}),
+	]);
+	assert_eq!(harness.counter.focus_time(), 25 * 60);
+	assert_eq!(harness.counter.break_time(), 10 * 60 / 5);
+	assert_eq!(harness.counter.pom(), 1);
+
+	// if not focusing then there is nothing to abandon
+	events.clear();
+	harness.counter.abandon(|event| events.push(event));
+	assert_eq!(events, []);
+
+	// if a break is skipped then go back to focusing with break kept
+	harness.counter.start();
+	harness.counter.toggle_break(|_| ());
+	harness.advance(30);
+	harness.counter.skip_break(|event| events.push(event));
+	assert_eq!(
+		events[0],
+		CounterEvent::PhaseEnded(PhaseSummary {
+			phase: Phase::Break,
+			outcome: PhaseOutcome::Skipped,
+			started: Some(harness.clock.now() - Duration::from_secs(30)),
+			ended: Some(harness.clock.now()),
+			counted: 30,
+			interruptions: Interruptions::default(),
+			clover: false,
+		})
+	);
+	assert_eq!(harness.counter.work_state(), CounterWorkState::Focusing);
+	assert_eq!(harness.counter.break_time(), 10 * 60 / 5 - 30);
+
+	// if rewound then progress should return but time should not be recounted
+	let earlier = harness.counter.clone();
+	harness.advance(60);
+	harness.counter.toggle_active();
+	assert_eq!(
+		harness.counter.rewind(earlier),
+		Some(Transition {
+			from: CounterWorkState::Paused { previous: Phase::Focus },
+			to: CounterWorkState::Focusing
+		})
+	);
+	assert_eq!(harness.counter.focus_time(), 25 * 60);
+	harness.advance(1);
+	assert_eq!(harness.counter.focus_time(), 25 * 60 - 1);
+}
+
+#[test]
+pub fn undo_ended_phase() {
+	let mut conn = connect();
+	let clock = MockClock::new(UNIX_EPOCH + Duration::from_secs(1_000_000));
+	let mut app = App::new(Counter::new());
+	let mut press = |app: &mut App, c| {
+		let code = match c {
+			'\n' => KeyCode::Enter,
+			c => KeyCode::Char(c),
+		};
+		app.handle_key_event(
+			KeyEvent::new(code, KeyModifiers::NONE),
+			&mut conn,
+		);
+	};
+	app.tick(&clock);
+
+	// if an action does not end a phase then it can be undone
+	press(&mut app, 'p');
+	clock.advance(Duration::from_secs(60));
+	app.tick(&clock);
+	press(&mut app, 'p');
+	press(&mut app, 'u');
+	assert_eq!(app.counter.work_state(), CounterWorkState::Focusing);
+
+	// if it does then it is recorded, so neither it nor anything before it
+	// can be undone
+	press(&mut app, 'a');
+	press(&mut app, 'u');
+	assert_eq!(app.counter.work_state(), CounterWorkState::Idle);
+	press(&mut app, 'p');
+	clock.advance(Duration::from_secs(25 * 60));
+	app.tick(&clock);
+	let outcomes: Vec<i32> =
+		app.take_sessions().iter().map(|session| session.outcome).collect();
+	assert_eq!(outcomes, [1, 0]);
+
+	// if an interruption is logged then it is recorded too
+	press(&mut app, 'p');
+	press(&mut app, 'i');
+	press(&mut app, '\n');
+	press(&mut app, 'u');
+	assert_eq!(app.counter.work_state(), CounterWorkState::Focusing);
+	assert_eq!(app.counter.interruptions().internal, 1);
+	assert_eq!(app.take_interruptions().len(), 1);
+}
+
+#[test]
+pub fn skip_classic_break() {
+	// if a classic break is skipped then it is forfeited
+	let mut harness = Harness::new(Counter::with_opts(&AppOpts {
+		mode: Some(CounterMode::Classic),
+		..Default::default()
+	}));
+	harness.counter.start();
+	harness.advance(25 * 60);
+	assert_eq!(harness.counter.work_state(), CounterWorkState::OnBreak);
+	harness.counter.skip_break(|_| ());
+	assert_eq!(harness.counter.work_state(), CounterWorkState::Focusing);
+	assert_eq!(harness.counter.break_time(), 0);
+}
+
+#[test]
+pub fn auto_start() {
+	// if auto break is set then a completed pom starts the break earned
+	let mut harness = Harness::new(Counter::with_opts(&AppOpts {
+		auto_break: Some(true),
+		auto_focus: Some(true),
+		..Default::default()
+	}));
+	harness.counter.start();
+	harness.advance(25 * 60);
+	assert_eq!(harness.counter.work_state(), CounterWorkState::OnBreak);
+	assert_eq!(harness.counter.break_time(), 5 * 60);
+	// if auto focus is set then an exhausted break starts the next session
+	harness.advance(5 * 60);
+	assert_eq!(harness.counter.work_state(), CounterWorkState::Focusing);
+	assert_eq!(harness.counter.focus_time(), 25 * 60);
+
+	// classic breaks also resume focus instead of waiting for the user
+	let mut harness = Harness::new(Counter::with_opts(&AppOpts {
+		mode: Some(CounterMode::Classic),
+		auto_focus: Some(true),
+		..Default::default()
+	}));
+	assert_eq!(harness.counter.break_policy(), BreakPolicy::Resume);
+	harness.counter.start();
+	harness.advance(25 * 60);
+	assert_eq!(harness.counter.work_state(), CounterWorkState::OnBreak);
+	harness.advance(5 * 60);
+	assert_eq!(harness.counter.work_state(), CounterWorkState::Focusing);
+
+	// without auto break, a completed pom waits for the user
+	let mut harness = Harness::new(Counter::new());
+	harness.counter.start();
+	harness.advance(25 * 60);
+	assert!(!harness.counter.work_state().is_active());
+	assert!(!harness.counter.auto_break() && !harness.counter.auto_focus());
+}
+
+#[test]
+pub fn interruptions() {
+	let mut harness = Harness::new(Counter::new());
+	let start = harness.clock.now();
+	// if not in a focus session then interruptions are not counted
+	assert_eq!(harness.counter.interrupt(InterruptionKind::Internal), None);
+	assert_eq!(harness.counter.session_start(), None);
+
+	// if focusing then interruptions are counted against the session
+	harness.counter.start();
+	assert_eq!(harness.counter.session_start(), Some(start));
+	harness.advance(60);
+	assert_eq!(
+		harness.counter.interrupt(InterruptionKind::Internal),
+		Some(start + Duration::from_secs(60))
+	);
+	harness.counter.toggle_active();
+	harness.counter.interrupt(InterruptionKind::External);
+	harness.counter.interrupt(InterruptionKind::External);
+	assert_eq!(harness.counter.interruptions(), Interruptions {
+		internal: 1,
+		external: 2
+	});
+
+	// if the session is resumed after a break then it is the same session
+	harness.counter.toggle_active();
+	harness.counter.toggle_break(|_| ());
+	assert_eq!(harness.counter.interrupt(InterruptionKind::Internal), None);
+	harness.advance(5);
+	harness.counter.toggle_break(|_| ());
+	assert_eq!(harness.counter.session_start(), Some(start));
+
+	// if restored then the session and its interruptions are kept
+	let mut restored = Counter::new();
+	restored.restore(
+		harness.counter.snapshot(harness.clock.now()),
+		start,
+		|_| (),
+	);
+	assert_eq!(restored.session_start(), Some(start));
+	assert_eq!(restored.interruptions(), harness.counter.interruptions());
+
+	// if the session ends then the next one starts afresh
+	harness.advance(25 * 60);
+	assert_eq!(harness.counter.session_start(), None);
+	assert_eq!(harness.counter.interruptions(), Interruptions::default());
+	harness.counter.start();
+	assert_eq!(harness.counter.session_start(), Some(harness.clock.now()));
+}
+
+#[test]
+pub fn phase_history() {
+	// if a pom is completed then its whole session is recorded, with time
+	// spent paused left out of what was counted
+	let mut harness = Harness::new(Counter::new());
+	harness.counter.start();
+	harness.advance(10 * 60);
+	harness.counter.toggle_active();
+	harness.advance(60);
+	harness.counter.toggle_active();
+	harness.advance(15 * 60);
+	assert_eq!(harness.take_phases(), [(
+		Phase::Focus,
+		PhaseOutcome::Completed,
+		26 * 60,
+		25 * 60
+	)]);
+
+	// if a break is spent then it is completed
+	harness.counter.start();
+	harness.counter.toggle_break(|_| ());
+	harness.advance(5 * 60 + 1);
+	assert_eq!(harness.take_phases(), [
+		(Phase::Break, PhaseOutcome::Completed, 5 * 60, 5 * 60),
+		(Phase::Focus, PhaseOutcome::Interrupted, 5 * 60, 0),
+	]);
+
+	// if a break is ended early then it is skipped
+	let mut events = Vec::new();
+	harness.counter.start();
+	harness.advance(60);
+	harness.counter.toggle_break(|_| ());
+	harness.advance(5);
+	harness.counter.toggle_break(|event| events.push(event));
+	assert_eq!(harness.counter.work_state(), CounterWorkState::Focusing);
+	assert_eq!(phases_ended(&events), [(Phase::Break, PhaseOutcome::Skipped)]);
+
+	// if a flowtime session is ended too soon then it is interrupted
+	let mut harness = Harness::new(Counter::with_opts(&AppOpts {
+		mode: Some(CounterMode::Flowtime),
+		..Default::default()
+	}));
+	let mut events = Vec::new();
+	harness.counter.start();
+	harness.advance(60);
+	harness.counter.end_session(|event| events.push(event));
+	harness.counter.start();
+	harness.advance(20 * 60);
+	harness.counter.end_session(|event| events.push(event));
+	assert_eq!(phases_ended(&events), [
+		(Phase::Focus, PhaseOutcome::Interrupted),
+		(Phase::Focus, PhaseOutcome::Completed),
+	]);
+
+	// if overdrawn then ending the break completes it
+	let mut harness = with_break_policy(BreakPolicy::Overdraft);
+	let mut events = Vec::new();
+	harness.advance(20);
+	harness.counter.toggle_break(|event| events.push(event));
+	assert_eq!(harness.counter.overdraft(), 10);
+	assert_eq!(phases_ended(&events), [(
+		Phase::Break,
+		PhaseOutcome::Completed
+	)]);
+}
+
+#[test]
+pub fn reconfigure() {
+	let mut harness = Harness::new(Counter::new());
+	harness.counter.start();
+	harness.advance(10 * 60);
+	let break_time = harness.counter.break_time();
+
+	// if the session is made longer then the focus so far still counts
+	harness.counter.reconfigure(&AppOpts {
+		focus_time: Some(50 * 60),
+		clover_length: Some(2),
+		..Default::default()
+	});
+	assert_eq!(harness.counter.focused(), 10 * 60);
+	assert_eq!(harness.counter.focus_time(), 40 * 60);
+	assert_eq!(harness.counter.break_time(), break_time);
+	assert_eq!(harness.counter.clover_length(), 2);
+	assert_eq!(harness.counter.work_state(), CounterWorkState::Focusing);
+
+	// if it is made shorter than the focus so far then it ends on the next
+	// tick
+	harness.counter.reconfigure(&AppOpts {
+		focus_time: Some(5 * 60),
+		..Default::default()
+	});
+	assert_eq!(harness.counter.focus_time(), 1);
+	harness.advance(1);
+	assert_eq!(harness.counter.pom(), 2);
+
+	// if the mode is changed then the focus so far carries over into it
+	harness.counter.start();
+	harness.advance(60);
+	harness.counter.reconfigure(&AppOpts {
+		mode: Some(CounterMode::Flowtime),
+		..Default::default()
+	});
+	assert_eq!(harness.counter.mode(), CounterMode::Flowtime);
+	assert_eq!(harness.counter.focus_time(), 60);
+	assert_eq!(harness.counter.focused(), 60);
+}
+
+fn phases_ended(events: &[CounterEvent]) -> Vec<(Phase, PhaseOutcome)> {
+	events
+		.iter()
+		.filter_map(|event| match event {
+			CounterEvent::PhaseEnded(summary) => {
+				Some((summary.phase, summary.outcome))
+			}
+			_ => None,
+		})
+		.collect()
 }
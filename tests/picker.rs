@@ -0,0 +1,53 @@
+use flussomodoro::{
+	db::Task,
+	forms::{fuzzy_score, TaskPicker},
+};
+
+fn task(id: i32, name: &str) -> Task {
+	Task {
+		id,
+		name: name.to_string(),
+		focus_req: 4,
+		focus_done: 0,
+		box_cat: 0,
+		status: 0,
+		position: id,
+	}
+}
+
+#[test]
+pub fn scoring() {
+	assert_eq!(fuzzy_score("", "Chores"), Some(0));
+	assert!(fuzzy_score("CHR", "chores").is_some());
+	assert_eq!(fuzzy_score("hc", "Chores"), None);
+	assert_eq!(fuzzy_score("choress", "Chores"), None);
+	// a run of characters scores higher than the same characters spread out
+	assert!(fuzzy_score("rep", "Report") > fuzzy_score("rep", "Ruler tape"));
+	// as does matching the start of a word
+	assert!(fuzzy_score("t", "Write tests") > fuzzy_score("t", "Write"));
+}
+
+#[test]
+pub fn picking() {
+	let mut picker = TaskPicker::new(vec![
+		task(1, "Ruler tape"),
+		task(2, "Taxes"),
+		task(3, "Report"),
+	]);
+	assert_eq!(picker.matches().len(), 3);
+	assert_eq!(picker.picked().unwrap().id, 1);
+
+	picker.push('r');
+	picker.push('e');
+	picker.push('p');
+	let ids: Vec<i32> = picker.matches().iter().map(|task| task.id).collect();
+	assert_eq!(ids, [3, 1]);
+	picker.select_by(5);
+	assert_eq!(picker.picked().unwrap().id, 1);
+
+	// if the query changes then the best match is selected again
+	picker.push('x');
+	assert!(picker.picked().is_none());
+	picker.pop();
+	assert_eq!(picker.picked().unwrap().id, 3);
+}
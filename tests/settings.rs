@@ -0,0 +1,170 @@
+mod common;
+
+use std::time::{Duration, UNIX_EPOCH};
+
+use clap::Parser;
+use common::connect;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use flussomodoro::{
+	app::{App, AppOpts},
+	clock::MockClock,
+	counter::{BreakPolicy, Counter, CounterMode, CounterWorkState, Phase},
+	settings::{Setting, Theme},
+};
+
+fn env(vars: &[(&str, &str)]) -> AppOpts {
+	let vars = vars.iter().map(|&(var, value)| (var.into(), value.into()));
+	AppOpts::from_vars(vars).unwrap()
+}
+
+#[test]
+pub fn set_and_show() {
+	let mut opts = AppOpts::default();
+	Setting::FocusTime.set(&mut opts, " 3000 ").unwrap();
+	Setting::BreakRatio.set(&mut opts, "2.5").unwrap();
+	Setting::BreakWarnings.set(&mut opts, "300, 60,10").unwrap();
+	Setting::KanbanColumns.set(&mut opts, "Backlog, Doing,Done").unwrap();
+	let counter = Counter::with_opts(&opts);
+	assert_eq!(Setting::FocusTime.value(&opts, &counter), "3000");
+	assert_eq!(Setting::BreakRatio.value(&opts, &counter), "2.5");
+	assert_eq!(Setting::BreakWarnings.value(&opts, &counter), "300,60,10");
+	assert_eq!(opts.kanban_columns(), ["Backlog", "Doing", "Done"]);
+
+	// if the input is not valid then the setting is left as it was
+	assert!(Setting::FocusTime.set(&mut opts, "0").is_err());
+	assert!(Setting::CloverLength.set(&mut opts, "0").is_err());
+	assert!(Setting::BreakRatio.set(&mut opts, "-1").is_err());
+	assert!(Setting::BreakWarnings.set(&mut opts, "30,soon").is_err());
+	assert!(Setting::KanbanColumns.set(&mut opts, "To Do,,Done").is_err());
+	assert_eq!(opts.focus_time, Some(3000));
+	assert_eq!(opts.break_warnings, Some(vec![300, 60, 10]));
+}
+
+#[test]
+pub fn cycle() {
+	let mut opts = AppOpts::default();
+	let counter = Counter::with_opts(&opts);
+	assert_eq!(Setting::Notify.value(&opts, &counter), "off");
+	Setting::Notify.cycle(&mut opts, 1);
+	assert_eq!(opts.notify, Some(true));
+
+	Setting::Mode.cycle(&mut opts, 1);
+	assert_eq!(opts.mode, Some(CounterMode::Flowtime));
+	Setting::Mode.cycle(&mut opts, -2);
+	assert_eq!(opts.mode, Some(CounterMode::Classic));
+	Setting::BreakPolicy.cycle(&mut opts, -1);
+	assert_eq!(opts.break_policy, Some(BreakPolicy::Pause));
+	assert_eq!(Setting::Theme.value(&opts, &counter), "dark");
+	Setting::Theme.cycle(&mut opts, 1);
+	assert_eq!(opts.theme, Some(Theme::Light));
+	assert_eq!(Setting::Theme.value(&opts, &counter), "light");
+	Setting::Theme.cycle(&mut opts, 1);
+	assert_eq!(opts.theme, Some(Theme::Dark));
+
+	// a typed setting cannot be cycled
+	Setting::FocusTime.cycle(&mut opts, 1);
+	assert_eq!(opts.focus_time, None);
+}
+
+#[test]
+pub fn layers() {
+	let mut conn = connect();
+	AppOpts {
+		focus_time: Some(1000),
+		short_break: Some(200),
+		long_break: Some(600),
+		..Default::default()
+	}
+	.save_config(&mut conn)
+	.unwrap();
+	let env = env(&[
+		("FLUSSOMODORO_SHORT_BREAK", "250"),
+		("FLUSSOMODORO_LONG_BREAK", "700"),
+		("FLUSSOMODORO_BREAK_WARNINGS", "60,10"),
+		("FLUSSOMODORO_THEME", "light"),
+		("FLUSSOMODORO_UNKNOWN", "1"),
+		("HOME", "/root"),
+	]);
+
+	// the command line beats the environment, which beats what was saved
+	let mut opts = AppOpts::parse_from(["flussomodoro", "-L", "800", "-n"]);
+	opts.load_config(&mut conn, &env).unwrap();
+	assert_eq!(opts.focus_time, Some(1000));
+	assert_eq!(opts.short_break, Some(250));
+	assert_eq!(opts.long_break, Some(800));
+	assert_eq!(opts.break_warnings, Some(vec![60, 10]));
+	assert_eq!(opts.notify, Some(true));
+	assert_eq!(opts.theme, Some(Theme::Light));
+	// and whatever is not given anywhere is left to its default
+	assert_eq!(opts.mode, None);
+
+	// if not asked to then nothing given is saved
+	let mut opts = AppOpts::default();
+	opts.load_config(&mut conn, &AppOpts::default()).unwrap();
+	assert_eq!(opts.long_break, Some(600));
+	assert_eq!(opts.notify, None);
+
+	// if asked to then only what was given on the command line is saved
+	let mut opts = AppOpts::parse_from([
+		"flussomodoro",
+		"-L",
+		"800",
+		"--theme",
+		"light",
+		"--save",
+	]);
+	opts.load_config(&mut conn, &env).unwrap();
+	let mut opts = AppOpts::default();
+	opts.load_config(&mut conn, &AppOpts::default()).unwrap();
+	assert_eq!(opts.long_break, Some(800));
+	assert_eq!(opts.short_break, Some(200));
+	assert_eq!(opts.theme, Some(Theme::Light));
+}
+
+#[test]
+pub fn invalid_env() {
+	let vars = [("FLUSSOMODORO_CLOVER_LENGTH".to_string(), "0".to_string())];
+	assert!(AppOpts::from_vars(vars).is_err());
+	let vars = [("FLUSSOMODORO_MODE".to_string(), "sometimes".to_string())];
+	assert!(AppOpts::from_vars(vars).is_err());
+	let vars = [("FLUSSOMODORO_THEME".to_string(), "neon".to_string())];
+	assert!(AppOpts::from_vars(vars).is_err());
+	let vars = [("FLUSSOMODORO_AUTO_BREAK".to_string(), "false".to_string())];
+	assert_eq!(AppOpts::from_vars(vars).unwrap().auto_break, Some(false));
+}
+
+#[test]
+pub fn undo_keeps_settings() {
+	let mut conn = connect();
+	let clock = MockClock::new(UNIX_EPOCH + Duration::from_secs(1_000_000));
+	let mut app = App::default();
+	app.tick(&clock);
+	let mut press = |app: &mut App, code| {
+		let event = KeyEvent::new(code, KeyModifiers::NONE);
+		app.handle_key_event(event, &mut conn);
+	};
+	press(&mut app, KeyCode::Char('p'));
+	clock.advance(Duration::from_secs(60));
+	app.tick(&clock);
+	press(&mut app, KeyCode::Char('p'));
+
+	// if a setting is changed after an action then undoing the action keeps
+	// the setting
+	press(&mut app, KeyCode::Char('o'));
+	press(&mut app, KeyCode::Down);
+	press(&mut app, KeyCode::Enter);
+	press(&mut app, KeyCode::Backspace);
+	press(&mut app, KeyCode::Backspace);
+	press(&mut app, KeyCode::Backspace);
+	press(&mut app, KeyCode::Backspace);
+	"600".chars().for_each(|c| press(&mut app, KeyCode::Char(c)));
+	press(&mut app, KeyCode::Enter);
+	assert_eq!(app.opts.focus_time, Some(600));
+	assert_eq!(app.counter.work_state(), CounterWorkState::Paused {
+		previous: Phase::Focus
+	});
+	press(&mut app, KeyCode::Char('u'));
+	assert_eq!(app.counter.work_state(), CounterWorkState::Focusing);
+	assert_eq!(app.counter.original_focus_time(), 600);
+	assert_eq!(app.counter.focus_time(), 540);
+}
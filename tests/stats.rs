@@ -0,0 +1,76 @@
+use chrono::{DateTime, NaiveDate};
+use flussomodoro::{
+	db::Session,
+	stats::{Stats, HISTORY_DAYS},
+};
+
+const DAY: i64 = 24 * 60 * 60;
+
+/// A session on the `day`th day after the epoch, in UTC.
+fn session(day: i64, kind: i32, outcome: i32, counted: i32) -> Session {
+	Session {
+		id: 0,
+		started: day * DAY + 60,
+		ended: day * DAY + 60 + i64::from(counted),
+		kind,
+		outcome,
+		task_id: None,
+		internal_interruptions: 0,
+		external_interruptions: 0,
+		counted,
+		clover: false,
+	}
+}
+
+fn stats_of(sessions: &[Session], today: i64) -> Stats {
+	let day_of = |secs| DateTime::from_timestamp(secs, 0).unwrap().date_naive();
+	Stats::new(sessions, day_of(today * DAY), day_of)
+}
+
+#[test]
+pub fn daily_totals() {
+	let stats = stats_of(
+		&[
+			// too long ago to count
+			session(100 - HISTORY_DAYS as i64, 0, 0, 1500),
+			// completes the clover started before the history
+			Session { clover: true, ..session(98, 0, 0, 1500) },
+			session(98, 0, 1, 600),
+			session(98, 1, 0, 300),
+			session(100, 0, 0, 1500),
+			Session { clover: true, ..session(100, 0, 0, 1500) },
+			session(100, 1, 2, 100),
+		],
+		100,
+	);
+
+	assert_eq!(stats.today, NaiveDate::from_ymd_opt(1970, 4, 11).unwrap());
+	assert_eq!(stats.daily_focus.len(), HISTORY_DAYS);
+	assert_eq!(stats.poms_today(), 2);
+	assert_eq!(stats.focus_today(), 3000);
+	assert_eq!(stats.daily_focus[HISTORY_DAYS - 3], 2100);
+	assert_eq!(stats.daily_poms[HISTORY_DAYS - 3], 1);
+	assert_eq!(stats.poms(), 3);
+	assert_eq!(stats.clovers, 2);
+	assert_eq!(stats.average_break(), 200);
+	assert_eq!(stats.break_usage(), 400.0 / 5100.0);
+	// a day was missed in between
+	assert_eq!(stats.streak, 1);
+}
+
+#[test]
+pub fn streak() {
+	let sessions: Vec<Session> =
+		(95..100).map(|day| session(day, 0, 0, 1500)).collect();
+
+	// if nothing is done yet today then the streak carries on from yesterday
+	assert_eq!(stats_of(&sessions, 100).streak, 5);
+	// if a whole day is missed then the streak is broken
+	assert_eq!(stats_of(&sessions, 101).streak, 0);
+	// if a session was not completed then it does not count
+	let mut sessions = sessions;
+	sessions.push(session(100, 0, 1, 600));
+	assert_eq!(stats_of(&sessions, 100).streak, 5);
+	sessions.push(session(100, 0, 0, 1500));
+	assert_eq!(stats_of(&sessions, 100).streak, 6);
+}
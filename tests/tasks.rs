@@ -0,0 +1,368 @@
+mod common;
+
+use std::time::{Duration, UNIX_EPOCH};
+
+use common::connect;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use diesel::SqliteConnection;
+use flussomodoro::{
+	app::{App, AppOpts},
+	clock::MockClock,
+	counter::{
+		CounterMode,
+		InterruptionKind,
+		Interruptions,
+		Phase,
+		PhaseOutcome,
+		PhaseSummary,
+	},
+	db::*,
+	forms::{TaskField, TaskForm},
+};
+
+fn press(app: &mut App, conn: &mut SqliteConnection, code: KeyCode) {
+	app.handle_key_event(KeyEvent::new(code, KeyModifiers::NONE), conn);
+}
+
+#[test]
+pub fn create_and_get() {
+	let mut conn = connect();
+	let task = create_task(&mut conn, "Write report".into(), 4, 0, 1).unwrap();
+	assert_eq!(get_task(&mut conn, task.id).unwrap().name, "Write report");
+	assert_eq!(get_tasks(&mut conn).unwrap().len(), 1);
+
+	// if a task breaks the constraints of the table then it is not created
+	assert!(matches!(
+		create_task(&mut conn, "".into(), 4, 0, 0),
+		Err(TaskError::InvalidName)
+	));
+	assert!(matches!(
+		create_task(&mut conn, "A name far too long".into(), 4, 0, 0),
+		Err(TaskError::InvalidName)
+	));
+	assert!(matches!(
+		create_task(&mut conn, "Chores".into(), 2, 3, 0),
+		Err(TaskError::InvalidProgress { focus_done: 3, focus_req: 2 })
+	));
+	assert!(matches!(
+		create_task(&mut conn, "Chores".into(), 0, 0, 0),
+		Err(TaskError::InvalidProgress { .. })
+	));
+	assert!(matches!(
+		create_task(&mut conn, "Chores".into(), 2, 0, 4),
+		Err(TaskError::InvalidQuadrant(4))
+	));
+	assert!(matches!(get_task(&mut conn, 42), Err(TaskError::NotFound(42))));
+}
+
+#[test]
+pub fn update() {
+	let mut conn = connect();
+	let task = create_task(&mut conn, "Chores".into(), 2, 1, 0).unwrap();
+
+	let task = rename_task(&mut conn, task.id, "Laundry".into()).unwrap();
+	assert_eq!(task.name, "Laundry");
+	let task = move_task(&mut conn, task.id, 3).unwrap();
+	assert_eq!(task.box_cat, 3);
+	let task = update_task(&mut conn, task.id, TaskChanges {
+		focus_req: Some(5),
+		..Default::default()
+	})
+	.unwrap();
+	assert_eq!((task.focus_done, task.focus_req), (1, 5));
+	// if there are no changes then the task is as it was
+	let same = update_task(&mut conn, task.id, TaskChanges::default()).unwrap();
+	assert_eq!((same.id, same.name, same.box_cat), (task.id, task.name, 3));
+	assert_eq!((same.focus_done, same.focus_req), (1, 5));
+
+	// if changes would break the constraints then nothing changes
+	assert!(matches!(
+		update_task(&mut conn, task.id, TaskChanges {
+			name: Some("Ironing".into()),
+			focus_done: Some(6),
+			..Default::default()
+		}),
+		Err(TaskError::InvalidProgress { focus_done: 6, focus_req: 5 })
+	));
+	assert!(matches!(
+		move_task(&mut conn, task.id, -1),
+		Err(TaskError::InvalidQuadrant(-1))
+	));
+	assert_eq!(get_task(&mut conn, task.id).unwrap().name, "Laundry");
+	assert!(matches!(
+		rename_task(&mut conn, 42, "Laundry".into()),
+		Err(TaskError::NotFound(42))
+	));
+	assert!(matches!(
+		update_task(&mut conn, 42, TaskChanges::default()),
+		Err(TaskError::NotFound(42))
+	));
+}
+
+#[test]
+pub fn form() {
+	let mut conn = connect();
+
+	// if the form is new then saving it creates a task
+	let mut form = TaskForm::new();
+	form.name = " Write report ".into();
+	form.focus_req = "3".into();
+	form.move_field(-1);
+	assert_eq!(form.field, TaskField::Quadrant);
+	assert!(form.input().is_none());
+	form.cycle_quadrant(-1);
+	assert_eq!(form.value(TaskField::Quadrant), QUADRANT_NAMES[3]);
+	let task = form.save(&mut conn).unwrap();
+	assert_eq!(task.name, "Write report");
+	assert_eq!((task.focus_done, task.focus_req, task.box_cat), (0, 3, 3));
+
+	// if it edits a task then saving it updates that task
+	let mut form = TaskForm::edit(&task);
+	assert_eq!(form.value(TaskField::FocusReq), "3");
+	form.move_field(2);
+	form.input().unwrap().replace_range(.., "2");
+	let edited = form.save(&mut conn).unwrap();
+	assert_eq!(edited.id, task.id);
+	assert_eq!((edited.focus_done, edited.focus_req), (2, 3));
+	assert_eq!(get_tasks(&mut conn).unwrap().len(), 1);
+
+	// if the form is not valid then nothing is saved
+	form.focus_req = "lots".into();
+	assert!(matches!(
+		form.save(&mut conn),
+		Err(TaskError::InvalidCount(count)) if count == "lots"
+	));
+	form.focus_req = "1".into();
+	assert!(matches!(
+		form.save(&mut conn),
+		Err(TaskError::InvalidProgress { focus_done: 2, focus_req: 1 })
+	));
+	let mut form = TaskForm::new();
+	assert!(matches!(form.save(&mut conn), Err(TaskError::InvalidName)));
+	form.name = "Chores".into();
+	form.focus_done = " 1 ".into();
+	assert_eq!(form.save(&mut conn).unwrap().focus_done, 1);
+	assert_eq!(get_task(&mut conn, task.id).unwrap().focus_done, 2);
+
+	// if the task was deleted in the meantime then it is not recreated
+	delete_task(&mut conn, task.id).unwrap();
+	assert!(matches!(
+		TaskForm::edit(&edited).save(&mut conn),
+		Err(TaskError::NotFound(id)) if id == task.id
+	));
+}
+
+#[test]
+pub fn matrix() {
+	let mut conn = connect();
+	let first = create_task(&mut conn, "Chores".into(), 2, 0, 0).unwrap();
+	let second = create_task(&mut conn, "Taxes".into(), 2, 0, 0).unwrap();
+	let mut app = App::default();
+	app.load_tasks(&mut conn);
+	press(&mut app, &mut conn, KeyCode::Tab);
+	let box_cats = |app: &App| -> Vec<i32> {
+		app.tasks.iter().map(|task| task.box_cat).collect()
+	};
+
+	// if a task is moved to a quadrant then the cursor follows it there
+	press(&mut app, &mut conn, KeyCode::Char('4'));
+	assert_eq!(box_cats(&app), [3, 0]);
+	press(&mut app, &mut conn, KeyCode::Char('2'));
+	assert_eq!(box_cats(&app), [1, 0]);
+	assert_eq!(get_task(&mut conn, first.id).unwrap().box_cat, 1);
+
+	// if the quadrant in focus is empty then there is nothing to move
+	press(&mut app, &mut conn, KeyCode::Right);
+	press(&mut app, &mut conn, KeyCode::Char('1'));
+	assert_eq!(box_cats(&app), [1, 0]);
+	press(&mut app, &mut conn, KeyCode::Left);
+	press(&mut app, &mut conn, KeyCode::Left);
+	press(&mut app, &mut conn, KeyCode::Char('3'));
+	assert_eq!(box_cats(&app), [1, 2]);
+	assert_eq!(get_task(&mut conn, second.id).unwrap().box_cat, 2);
+}
+
+#[test]
+pub fn progress() {
+	let mut conn = connect();
+	let task = create_task(&mut conn, "Chores".into(), 2, 0, 0).unwrap();
+
+	let task = increment_focus_done(&mut conn, task.id).unwrap();
+	assert_eq!(task.focus_done, 1);
+	assert!(!task.is_complete());
+	let task = increment_focus_done(&mut conn, task.id).unwrap();
+	assert!(task.is_complete());
+	// if all of the focus sessions are done then there are none left to do
+	assert!(matches!(
+		increment_focus_done(&mut conn, task.id),
+		Err(TaskError::AlreadyComplete(id)) if id == task.id
+	));
+
+	let task = create_task(&mut conn, "Taxes".into(), 8, 1, 0).unwrap();
+	let task = complete_task(&mut conn, task.id).unwrap();
+	assert_eq!(task.focus_done, 8);
+	assert!(matches!(
+		complete_task(&mut conn, 42),
+		Err(TaskError::NotFound(42))
+	));
+}
+
+#[test]
+pub fn credit() {
+	let mut conn = connect();
+	let task = create_task(&mut conn, "Chores".into(), 1, 0, 0).unwrap();
+	let then = UNIX_EPOCH + Duration::from_secs(1_000_000);
+	let mut record = |phase, outcome, task_id| {
+		let summary = PhaseSummary {
+			phase,
+			outcome,
+			started: Some(then),
+			ended: Some(then + Duration::from_secs(1500)),
+			counted: 1500,
+			interruptions: Interruptions::default(),
+			clover: false,
+		};
+		let session = create_session(
+			&mut conn,
+			NewSession::new(&summary, task_id).unwrap(),
+		)
+		.unwrap();
+		credit_session(&mut conn, &session).unwrap()
+	};
+
+	// only completed focus sessions count towards a task
+	assert!(record(Phase::Focus, PhaseOutcome::Interrupted, Some(task.id))
+		.is_none());
+	assert!(
+		record(Phase::Break, PhaseOutcome::Completed, Some(task.id)).is_none()
+	);
+	assert!(record(Phase::Focus, PhaseOutcome::Completed, None).is_none());
+	let credited = record(Phase::Focus, PhaseOutcome::Completed, Some(task.id));
+	assert_eq!(credited.unwrap().focus_done, 1);
+	// if the task is already complete then the session is still recorded
+	assert!(
+		record(Phase::Focus, PhaseOutcome::Completed, Some(task.id)).is_none()
+	);
+	assert!(record(Phase::Focus, PhaseOutcome::Completed, Some(42)).is_none());
+	assert_eq!(get_task(&mut conn, task.id).unwrap().focus_done, 1);
+}
+
+#[test]
+pub fn credit_once() {
+	let mut conn = connect();
+	let task = create_task(&mut conn, "Chores".into(), 4, 0, 0).unwrap();
+	let clock = MockClock::new(UNIX_EPOCH + Duration::from_secs(1_000_000));
+	let mut app = App::with_opts(&AppOpts {
+		mode: Some(CounterMode::Flowtime),
+		..Default::default()
+	});
+	app.selected_task = Some(task.id);
+	app.tick(&clock);
+
+	// if the end of a session is undone then it is still only credited once
+	press(&mut app, &mut conn, KeyCode::Char('p'));
+	clock.advance(Duration::from_secs(30 * 60));
+	app.tick(&clock);
+	for c in ['e', 'u', 'e'] {
+		press(&mut app, &mut conn, KeyCode::Char(c));
+	}
+	for session in app.take_sessions() {
+		let session = create_session(&mut conn, session).unwrap();
+		credit_session(&mut conn, &session).unwrap();
+	}
+	assert_eq!(get_task(&mut conn, task.id).unwrap().focus_done, 1);
+}
+
+#[test]
+pub fn interruption_links() {
+	let mut conn = connect();
+	let then = UNIX_EPOCH + Duration::from_secs(1_000_000);
+	let interrupt = |conn: &mut _, secs, kind| {
+		let at = then + Duration::from_secs(secs);
+		create_interruption(conn, NewInterruption::new(then, at, kind)).unwrap()
+	};
+	let summary = PhaseSummary {
+		phase: Phase::Focus,
+		outcome: PhaseOutcome::Completed,
+		started: Some(then),
+		ended: Some(then + Duration::from_secs(1500)),
+		counted: 1500,
+		interruptions: Interruptions { internal: 1, external: 1 },
+		clover: false,
+	};
+
+	// if the session is not saved yet then it is linked once it is
+	let before = interrupt(&mut conn, 60, InterruptionKind::Internal);
+	assert_eq!(before.session_id, None);
+	let session =
+		create_session(&mut conn, NewSession::new(&summary, None).unwrap())
+			.unwrap();
+	// and if it is saved already then it is linked straight away
+	let after = interrupt(&mut conn, 1500, InterruptionKind::External);
+	assert_eq!(after.session_id, Some(session.id));
+
+	let linked = get_interruptions(&mut conn, session.id).unwrap();
+	let ids: Vec<i32> = linked.iter().map(|x| x.id).collect();
+	assert_eq!(ids, [before.id, after.id]);
+	assert!(get_interruptions(&mut conn, 42).unwrap().is_empty());
+}
+
+#[test]
+pub fn delete() {
+	let mut conn = connect();
+	let task = create_task(&mut conn, "Chores".into(), 2, 0, 0).unwrap();
+	let then = UNIX_EPOCH + Duration::from_secs(1_000_000);
+	let summary = PhaseSummary {
+		phase: Phase::Focus,
+		outcome: PhaseOutcome::Completed,
+		started: Some(then),
+		ended: Some(then + Duration::from_secs(1500)),
+		counted: 1500,
+		interruptions: Interruptions::default(),
+		clover: false,
+	};
+	create_session(
+		&mut conn,
+		NewSession::new(&summary, Some(task.id)).unwrap(),
+	)
+	.unwrap();
+
+	// if a task is deleted then its sessions are kept, but unlinked
+	delete_task(&mut conn, task.id).unwrap();
+	assert!(get_tasks(&mut conn).unwrap().is_empty());
+	let sessions =
+		get_sessions(&mut conn, then, then + Duration::from_secs(1)).unwrap();
+	assert_eq!(sessions[0].task_id, None);
+	assert!(matches!(
+		delete_task(&mut conn, task.id),
+		Err(TaskError::NotFound(id)) if id == task.id
+	));
+}
+
+#[test]
+pub fn kanban() {
+	let mut conn = connect();
+	let first = create_task(&mut conn, "Chores".into(), 2, 0, 0).unwrap();
+	let second = create_task(&mut conn, "Taxes".into(), 2, 0, 0).unwrap();
+	assert_eq!((first.status, second.status), (0, 0));
+	assert!(first.position < second.position);
+
+	let first = swap_tasks(&mut conn, first.id, second.id).unwrap();
+	let second = get_task(&mut conn, second.id).unwrap();
+	assert!(first.position > second.position);
+
+	// if a task is moved to another column then it goes to the end of it
+	let third = create_task(&mut conn, "Laundry".into(), 2, 0, 0).unwrap();
+	let third = set_task_status(&mut conn, third.id, 1).unwrap();
+	let second = set_task_status(&mut conn, second.id, 1).unwrap();
+	assert_eq!(second.status, 1);
+	assert!(second.position > third.position);
+	assert!(matches!(
+		set_task_status(&mut conn, first.id, -1),
+		Err(TaskError::InvalidStatus(-1))
+	));
+	assert!(matches!(
+		swap_tasks(&mut conn, first.id, 42),
+		Err(TaskError::NotFound(42))
+	));
+}
@@ -5,10 +5,10 @@ use std::fmt::{self, Display, Formatter};
 /// For second values, prefer `SSs`
 /// For minute values, prefer `MM:SS`
 /// For hour values, prefer `HH:MM:SS`
-pub struct FormattedTime(u16);
+pub struct FormattedTime(u32);
 
-impl From<u16> for FormattedTime {
-	fn from(x: u16) -> Self {
+impl From<u32> for FormattedTime {
+	fn from(x: u32) -> Self {
 		FormattedTime(x)
 	}
 }
@@ -63,5 +63,10 @@ mod tests {
 			FormattedTime::from(15 * 3600 + 32 * 60 + 32).to_string(),
 			"15:32:32"
 		);
+		assert_eq!(
+			FormattedTime::from(20 * 3600 + 32 * 60 + 32).to_string(),
+			"20:32:32"
+		);
+		assert_eq!(FormattedTime::from(100 * 3600).to_string(), "100:00:00");
 	}
 }
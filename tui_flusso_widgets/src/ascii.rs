@@ -200,6 +200,7 @@ mod font {
 			'y' => Y,
 			'z' => Z,
 			':' => COLON,
+			'-' => BRECT,
 			_ => SPACE,
 		}
 	}